@@ -0,0 +1,127 @@
+//! Evaluating every possible next move of a position
+
+use serde::Serialize;
+
+use crate::{
+    game::GameState,
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+};
+
+/// Judgement of a move compared to the best one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// No other move leads to a shorter solution
+    Best,
+    /// Costs one move compared to the best move
+    Mistake,
+    /// Costs two or more moves compared to the best move
+    Blunder,
+}
+
+impl Verdict {
+    /// Classifies a move by the number of moves it costs compared to the best move
+    pub fn from_loss(loss: usize) -> Self {
+        match loss {
+            0 => Verdict::Best,
+            1 => Verdict::Mistake,
+            _ => Verdict::Blunder,
+        }
+    }
+}
+
+/// Evaluation of a single candidate move
+#[derive(Debug, Clone, Serialize)]
+pub struct MoveEvaluation {
+    pub color: Color,
+    /// Number of flooded tiles after the move
+    pub flooded: usize,
+    /// Number of moves needed to solve the board after the move. Missing if it wasn't solved
+    pub remaining: Option<usize>,
+    /// If `remaining` is proven to be minimal
+    pub optimal: bool,
+    /// Number of moves the move costs compared to the best move. Missing unless the lengths of
+    /// all moves are proven to be minimal
+    pub loss: Option<usize>,
+    pub verdict: Option<Verdict>,
+}
+
+/// Colors that flood at least one more tile when chosen next
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{analysis::candidates, problem::Problem};
+///
+/// let instance: Problem = "012\n200\n111".parse().unwrap();
+/// assert_eq!(candidates(&instance), vec![1, 2]);
+/// ```
+pub fn candidates(board: &Problem) -> Vec<Color> {
+    GameState::new(&ClusterGraph::from_problem(board)).candidates()
+}
+
+/// Evaluates every [candidate](candidates) move of a board
+///
+/// `solve` is called with the board after each candidate move and returns the length of the
+/// shortest solution it found along with whether that length is proven to be minimal, or `None`
+/// if it found no solution. The evaluations are ordered from the best to the worst move, moves
+/// that could not be solved last.
+///
+/// The best move is only known if every move was solved minimally, as any move whose length is
+/// just an upper bound might turn out better. Otherwise no move gets a loss or verdict.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{analysis::{analyze, Verdict}, problem::Problem, rating::greedy};
+///
+/// let instance: Problem = "012\n200\n111".parse().unwrap();
+/// let evaluations = analyze(&instance, |board| Some((greedy(board).colors.len(), true)));
+/// assert_eq!(evaluations.len(), 2);
+/// assert_eq!(evaluations[0].verdict, Some(Verdict::Best));
+///
+/// // greedy solutions aren't necessarily minimal, so they don't tell which move is best
+/// let evaluations = analyze(&instance, |board| Some((greedy(board).colors.len(), false)));
+/// assert!(evaluations.iter().all(|evaluation| evaluation.remaining.is_some()));
+/// assert!(evaluations.iter().all(|evaluation| evaluation.verdict.is_none()));
+/// ```
+pub fn analyze<S>(board: &Problem, mut solve: S) -> Vec<MoveEvaluation>
+where
+    S: FnMut(&Problem) -> Option<(usize, bool)>,
+{
+    let graph = ClusterGraph::from_problem(board);
+    let game = GameState::new(&graph);
+    let mut evaluations: Vec<MoveEvaluation> = game
+        .candidates()
+        .into_iter()
+        .map(|color| {
+            let mut next = board.clone();
+            next.apply_color(color);
+            let solved = solve(&next);
+            MoveEvaluation {
+                color,
+                flooded: game.flooded_tiles() + game.gain(color),
+                remaining: solved.map(|(moves, _)| moves),
+                optimal: solved.is_some_and(|(_, optimal)| optimal),
+                loss: None,
+                verdict: None,
+            }
+        })
+        .collect();
+
+    let proven = evaluations.iter().all(|evaluation| evaluation.optimal);
+    let best = evaluations
+        .iter()
+        .filter_map(|evaluation| evaluation.remaining)
+        .min()
+        .filter(|_| proven);
+    for evaluation in evaluations.iter_mut() {
+        evaluation.loss = evaluation
+            .remaining
+            .zip(best)
+            .map(|(moves, best)| moves - best);
+        evaluation.verdict = evaluation.loss.map(Verdict::from_loss);
+    }
+
+    evaluations.sort_by_key(|evaluation| (evaluation.remaining.is_none(), evaluation.remaining));
+    evaluations
+}
@@ -0,0 +1,190 @@
+//! Solving many problem instances at once and tabulating the results
+
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use serde::Serialize;
+
+/// A single row of a batch results table
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRow {
+    /// Name of the instance, usually its file path
+    pub instance: String,
+    pub height: usize,
+    pub width: usize,
+    pub colors: usize,
+    pub clusters: usize,
+    /// Lower solution bound the search started with
+    pub lower_bound: usize,
+    /// Upper solution bound the search started with
+    pub upper_bound: usize,
+    /// Result of the last z3 run (or the last satisfiable one, if any): `sat`, `unsat` or `unknown`
+    pub result: String,
+    /// Length of the shortest solution found
+    pub best_length: Option<usize>,
+    /// If the best solution is proven to be of minimal length
+    pub optimal: bool,
+    /// Wall time spent on the instance
+    pub time_secs: f64,
+}
+
+/// A row of a results table, which is written by [write_csv] or [write_json]
+pub trait TableRow: Serialize {
+    /// Names of the columns
+    const HEADER: &'static [&'static str];
+
+    /// Values of the columns in the order of [TableRow::HEADER], unescaped
+    fn cells(&self) -> Vec<String>;
+}
+
+impl TableRow for BatchRow {
+    const HEADER: &'static [&'static str] = &[
+        "instance",
+        "height",
+        "width",
+        "colors",
+        "clusters",
+        "lower_bound",
+        "upper_bound",
+        "result",
+        "best_length",
+        "optimal",
+        "time_secs",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.instance.clone(),
+            self.height.to_string(),
+            self.width.to_string(),
+            self.colors.to_string(),
+            self.clusters.to_string(),
+            self.lower_bound.to_string(),
+            self.upper_bound.to_string(),
+            self.result.clone(),
+            self.best_length.map(|l| l.to_string()).unwrap_or_default(),
+            self.optimal.to_string(),
+            format!("{:.3}", self.time_secs),
+        ]
+    }
+}
+
+/// Writes `rows` as CSV table (including a header line)
+pub fn write_csv<W: Write, R: TableRow>(w: &mut W, rows: &[R]) -> std::io::Result<()> {
+    writeln!(w, "{}", R::HEADER.join(","))?;
+    for row in rows {
+        let cells: Vec<String> = row.cells().iter().map(|cell| csv_escape(cell)).collect();
+        writeln!(w, "{}", cells.join(","))?;
+    }
+    Ok(())
+}
+
+/// Writes `rows` as JSON array of objects
+pub fn write_json<W: Write, R: TableRow>(w: &mut W, rows: &[R]) -> std::io::Result<()> {
+    serde_json::to_writer_pretty(&mut *w, rows)?;
+    writeln!(w)
+}
+
+/// Quotes a CSV field if necessary
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Applies `f` to every item on a pool of `jobs` worker threads
+///
+/// Results are returned in the order of `items`.
+pub fn run_parallel<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(idx) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("Every item is processed by a worker"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn escapes_csv_fields() {
+        assert_eq!(csv_escape("instances/000.txt"), "instances/000.txt");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_escape(""), "");
+    }
+
+    #[test]
+    fn keeps_the_order_of_items() {
+        let items: Vec<u64> = (0..20).collect();
+        // later items finish first, so the results arrive out of order
+        let results = run_parallel(&items, 4, |item| {
+            std::thread::sleep(Duration::from_millis(20 - item));
+            item * 2
+        });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+
+        assert_eq!(run_parallel(&items, 0, |item| *item), items);
+        assert!(run_parallel(&[] as &[u64], 4, |item| *item).is_empty());
+    }
+
+    #[test]
+    fn writes_escaped_csv() {
+        let row = BatchRow {
+            instance: "a,b.txt".to_owned(),
+            height: 2,
+            width: 3,
+            colors: 2,
+            clusters: 4,
+            lower_bound: 0,
+            upper_bound: 3,
+            result: "sat".to_owned(),
+            best_length: Some(2),
+            optimal: true,
+            time_secs: 0.5,
+        };
+        let mut csv = vec![];
+        write_csv(&mut csv, &[row]).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "instance,height,width,colors,clusters,lower_bound,upper_bound,result,best_length,optimal,time_secs\n\
+             \"a,b.txt\",2,3,2,4,0,3,sat,2,true,0.500\n"
+        );
+    }
+}
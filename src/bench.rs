@@ -0,0 +1,140 @@
+//! Summarizing repeated solver runs into comparable benchmark tables
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::batch::TableRow;
+
+/// Measurements of a single solver run
+#[derive(Debug, Clone)]
+pub struct BenchRun {
+    pub time: Duration,
+    /// Number of z3 checks performed
+    pub z3_calls: usize,
+    /// Length of the shortest solution found
+    pub best_length: Option<usize>,
+}
+
+/// A single row of a benchmark table, summarizing all runs of one configuration
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchRow {
+    /// User supplied label, e.g. a commit hash
+    pub label: String,
+    pub instance: String,
+    pub strategy: String,
+    pub encoding: String,
+    pub runs: usize,
+    /// Length of the shortest solution found in any run
+    pub best_length: Option<usize>,
+    pub median_secs: f64,
+    pub min_secs: f64,
+    pub max_secs: f64,
+    /// Median number of z3 checks per run
+    pub z3_calls: usize,
+}
+
+impl BenchRow {
+    /// Summarizes the runs of one configuration
+    pub fn from_runs(
+        label: &str,
+        instance: &str,
+        strategy: &str,
+        encoding: &str,
+        runs: &[BenchRun],
+    ) -> Self {
+        let mut times: Vec<f64> = runs.iter().map(|run| run.time.as_secs_f64()).collect();
+        times.sort_by(f64::total_cmp);
+        let mut calls: Vec<usize> = runs.iter().map(|run| run.z3_calls).collect();
+        calls.sort();
+
+        Self {
+            label: label.to_owned(),
+            instance: instance.to_owned(),
+            strategy: strategy.to_owned(),
+            encoding: encoding.to_owned(),
+            runs: runs.len(),
+            best_length: runs.iter().filter_map(|run| run.best_length).min(),
+            median_secs: median(&times),
+            min_secs: times.first().copied().unwrap_or_default(),
+            max_secs: times.last().copied().unwrap_or_default(),
+            z3_calls: calls.get(calls.len() / 2).copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Median of sorted values
+fn median(sorted: &[f64]) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        n if n % 2 == 0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+        n => sorted[n / 2],
+    }
+}
+
+impl TableRow for BenchRow {
+    const HEADER: &'static [&'static str] = &[
+        "label",
+        "instance",
+        "strategy",
+        "encoding",
+        "runs",
+        "best_length",
+        "median_secs",
+        "min_secs",
+        "max_secs",
+        "z3_calls",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.instance.clone(),
+            self.strategy.clone(),
+            self.encoding.clone(),
+            self.runs.to_string(),
+            self.best_length.map(|l| l.to_string()).unwrap_or_default(),
+            format!("{:.3}", self.median_secs),
+            format!("{:.3}", self.min_secs),
+            format!("{:.3}", self.max_secs),
+            self.z3_calls.to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(secs: &[u64]) -> BenchRow {
+        let runs: Vec<BenchRun> = secs
+            .iter()
+            .enumerate()
+            .map(|(idx, secs)| BenchRun {
+                time: Duration::from_secs(*secs),
+                z3_calls: idx,
+                best_length: Some(5),
+            })
+            .collect();
+        BenchRow::from_runs("", "", "min", "default", &runs)
+    }
+
+    #[test]
+    fn summarizes_runs_by_their_median() {
+        let odd = row(&[3, 1, 2]);
+        assert_eq!(
+            (odd.median_secs, odd.min_secs, odd.max_secs),
+            (2.0, 1.0, 3.0)
+        );
+        assert_eq!(odd.z3_calls, 1);
+
+        let even = row(&[4, 1, 3, 2]);
+        assert_eq!(
+            (even.median_secs, even.min_secs, even.max_secs),
+            (2.5, 1.0, 4.0)
+        );
+        assert_eq!(even.runs, 4);
+
+        assert_eq!(row(&[]).median_secs, 0.0);
+    }
+}
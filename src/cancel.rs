@@ -0,0 +1,45 @@
+//! Cancelling running searches from another thread
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Cancels running searches from another thread
+///
+/// Cancelling stops the search, which then returns the best solution found so far. z3 searches
+/// also interrupt their running check. All clones of a handle share their state.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::cancel::CancelHandle;
+/// use color_flood_rs::problem::Problem;
+/// use color_flood_rs::search::{search, SearchOptions};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let cancel = CancelHandle::new();
+/// cancel.cancel();
+/// let options = SearchOptions { cancel: Some(cancel), ..Default::default() };
+/// let outcome = search(&instance, &options);
+/// assert_eq!(outcome.expanded, 0);
+/// assert!(!outcome.optimal);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops all searches using this handle
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,159 @@
+//! Analyzing moves and cluster graphs of instances
+
+#[cfg(feature = "z3")]
+use crate::{
+    analysis::{self, Verdict},
+    cli::AnalyzeArgs,
+    report::AnalysisReport,
+    solver::{self, SolveOptions},
+};
+use crate::{
+    cli::{
+        input::{instances_or_exit, validate_prefix},
+        Args, GraphArgs, OutputFormat,
+    },
+    graph::ClusterGraph,
+    report::{GraphNodeReport, GraphReport, InstanceInfo},
+    solution::Solution,
+};
+
+use super::print_json;
+
+/// Evaluates every possible next move of all instances, after playing `--prefix`
+#[cfg(feature = "z3")]
+pub(super) fn analyze_instances(args: &Args, analyze_args: &AnalyzeArgs) {
+    let instances = instances_or_exit(args);
+    validate_prefix(args, &instances);
+
+    for (idx, (name, instance)) in instances.iter().enumerate() {
+        let board = instance.after(args.prefix());
+        let evaluations = analysis::analyze(&board, |next| {
+            let options = SolveOptions {
+                time_limit: analyze_args.time_limit(),
+                ..Default::default()
+            };
+            let outcome = solver::solve(next, &options);
+            let solution = outcome.solution?;
+            Some((solution.colors.len(), outcome.optimal))
+        });
+
+        if args.format() == OutputFormat::Json {
+            print_json(&AnalysisReport {
+                instance: InstanceInfo::new(name, instance),
+                prefix: args.prefix().to_vec(),
+                moves: evaluations,
+            });
+            continue;
+        }
+
+        if idx > 0 {
+            println!();
+        }
+        if args.prefix().is_empty() {
+            println!("{name}\n\n{board}");
+        } else {
+            println!("{name} after {}\n\n{board}", Solution::from(args.prefix()));
+        }
+
+        if evaluations.is_empty() {
+            println!("Already solved");
+            continue;
+        }
+
+        println!("move   flooded  remaining  verdict");
+        for evaluation in evaluations {
+            let remaining = match evaluation.remaining {
+                Some(moves) if evaluation.optimal => moves.to_string(),
+                Some(moves) => format!("<={moves}"),
+                None => "?".to_owned(),
+            };
+            let verdict = match (evaluation.verdict, evaluation.loss) {
+                (Some(Verdict::Best), _) => "best".to_owned(),
+                (Some(Verdict::Mistake), Some(loss)) => format!("mistake (+{loss})"),
+                (Some(Verdict::Blunder), Some(loss)) => format!("blunder (+{loss})"),
+                _ => "unknown".to_owned(),
+            };
+            println!(
+                "{}{:<3} {:<8} {:<10} {verdict}",
+                Solution::from([evaluation.color]),
+                evaluation.color,
+                evaluation.flooded,
+                remaining,
+            );
+        }
+    }
+}
+
+/// Prints the cluster graph of all instances after the moves given by `--prefix`
+pub(super) fn print_graphs(args: &Args, graph_args: &GraphArgs) {
+    let instances = instances_or_exit(args);
+    validate_prefix(args, &instances);
+
+    for (idx, (name, instance)) in instances.iter().enumerate() {
+        let board = instance.after(args.prefix());
+        let graph = ClusterGraph::from_problem(&board);
+
+        if graph_args.dot() {
+            println!("// {name}");
+            print!("{}", graph.to_dot());
+            continue;
+        }
+
+        let distances = graph.distances(graph.start());
+        let report = GraphReport {
+            instance: InstanceInfo::new(name, instance),
+            prefix: args.prefix().to_vec(),
+            start: graph.start(),
+            edges: graph.edges().count(),
+            eccentricity: graph.eccentricity(graph.start()),
+            diameter: graph_args.diameter().then(|| graph.diameter()),
+            nodes: (0..graph.len())
+                .map(|node| GraphNodeReport {
+                    color: graph.color(node),
+                    size: graph.size(node),
+                    distance: distances[node],
+                    neighbours: graph.neighbours(node).to_vec(),
+                })
+                .collect(),
+        };
+
+        if args.format() == OutputFormat::Json {
+            print_json(&report);
+            continue;
+        }
+
+        if idx > 0 {
+            println!();
+        }
+        if args.prefix().is_empty() {
+            println!("{name}\n\n{board}");
+        } else {
+            println!("{name} after {}\n\n{board}", Solution::from(args.prefix()));
+        }
+        print!(
+            "nodes: {}, edges: {}, start: {}, eccentricity: {}",
+            report.nodes.len(),
+            report.edges,
+            report.start,
+            report.eccentricity,
+        );
+        match report.diameter {
+            Some(diameter) => println!(", diameter: {diameter}\n"),
+            None => println!("\n"),
+        }
+
+        println!("node   color  size  degree  distance  neighbours");
+        for (id, node) in report.nodes.iter().enumerate() {
+            let neighbours: Vec<String> = node.neighbours.iter().map(ToString::to_string).collect();
+            println!(
+                "{id:<6} {}{:<4} {:<5} {:<7} {:<9} {}",
+                Solution::from([node.color]),
+                node.color,
+                node.size,
+                node.neighbours.len(),
+                node.distance,
+                neighbours.join(","),
+            );
+        }
+    }
+}
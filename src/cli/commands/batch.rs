@@ -0,0 +1,180 @@
+//! Solving many instances and benchmarking strategies
+
+use std::time::Instant;
+
+use clap::ValueEnum;
+
+use crate::{
+    batch::{self, BatchRow, TableRow},
+    bench::{BenchRow, BenchRun},
+    cli::{
+        input::{instances_or_exit, load_file},
+        Args, BatchArgs, BenchArgs, OutputFormat,
+    },
+    cluster::Cluster,
+    problem::Problem,
+    report::result_name,
+    solver::{self, set_random_seed, Encoding, SolveOptions},
+};
+
+/// Solves every instance in a directory in parallel and prints a results table
+pub(super) fn solve_batch(args: &Args, batch_args: &BatchArgs) {
+    let mut paths = match std::fs::read_dir(batch_args.dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            eprintln!("Could not read {}: {err}", batch_args.dir().display());
+            std::process::exit(1);
+        }
+    };
+    paths.sort();
+
+    let mut instances = vec![];
+    for path in paths {
+        match load_file(&path) {
+            Ok(loaded) => instances.extend(loaded),
+            Err(err) => eprintln!("Skipping {}: {err}", path.display()),
+        }
+    }
+
+    let finished = std::sync::atomic::AtomicUsize::new(0);
+    let rows = batch::run_parallel(&instances, batch_args.jobs(), |(name, instance)| {
+        let row = solve_batch_instance(name, instance, batch_args);
+        let finished = finished.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        eprintln!(
+            "[{finished}/{}] {name}: {} ({:.1}s)",
+            instances.len(),
+            match (row.best_length, row.optimal) {
+                (Some(length), true) => format!("{length} moves, optimal"),
+                (Some(length), false) => format!("{length} moves"),
+                (None, _) => row.result.clone(),
+            },
+            row.time_secs
+        );
+        row
+    });
+
+    let result = match batch_args.output() {
+        Some(path) => std::fs::File::create(path)
+            .and_then(|mut file| write_table(&mut file, args.format(), &rows)),
+        None => write_table(&mut std::io::stdout().lock(), args.format(), &rows),
+    };
+    if let Err(err) = result {
+        eprintln!("Could not write results: {err}");
+        std::process::exit(1);
+    }
+}
+
+/// Solves a single instance of a batch within the batch's time limit
+fn solve_batch_instance(name: &str, instance: &Problem, batch_args: &BatchArgs) -> BatchRow {
+    let start = Instant::now();
+
+    let options = SolveOptions {
+        strategy: batch_args.strategy().strategy(),
+        time_limit: batch_args
+            .time_limit()
+            .or(batch_args.strategy().limits().time_limit()),
+        ..Default::default()
+    };
+    let outcome = solver::solve(instance, &options);
+
+    BatchRow {
+        instance: name.to_owned(),
+        height: instance.height(),
+        width: instance.width(),
+        colors: instance.num_colors(),
+        clusters: Cluster::from_problem(instance).len(),
+        lower_bound: outcome.bounds.0,
+        upper_bound: outcome.bounds.1,
+        result: result_name(outcome.result).to_owned(),
+        best_length: outcome.solution.map(|solution| solution.colors.len()),
+        optimal: outcome.optimal,
+        time_secs: start.elapsed().as_secs_f64(),
+    }
+}
+
+/// Writes a batch or benchmark table in the given format
+fn write_table<W: std::io::Write, R: TableRow>(
+    w: &mut W,
+    format: OutputFormat,
+    rows: &[R],
+) -> std::io::Result<()> {
+    match format {
+        OutputFormat::Text => batch::write_csv(w, rows),
+        OutputFormat::Json => batch::write_json(w, rows),
+    }
+}
+
+/// Runs every configured strategy and encoding on all instances and prints a benchmark table
+pub(super) fn run_bench(args: &Args, bench_args: &BenchArgs) {
+    let instances = instances_or_exit(args);
+
+    let mut rows = vec![];
+    for (name, instance) in instances.iter() {
+        for strategy in bench_args.strategies() {
+            let solve_strategy = strategy.to_strategy();
+            for encoding in bench_args.encodings() {
+                // encoding options only affect the optimizer
+                if !solve_strategy.use_optimizer() && encoding.to_encoding() != Encoding::default()
+                {
+                    continue;
+                }
+
+                let mut runs = vec![];
+                for seed in bench_args.seeds() {
+                    for _ in 0..bench_args.repetitions() {
+                        set_random_seed(*seed);
+
+                        let options = SolveOptions {
+                            strategy: solve_strategy,
+                            encoding: encoding.to_encoding(),
+                            prefix: vec![],
+                            time_limit: bench_args.time_limit(),
+                            ..Default::default()
+                        };
+                        let start = Instant::now();
+                        let outcome = solver::solve(instance, &options);
+                        runs.push(BenchRun {
+                            time: start.elapsed(),
+                            z3_calls: outcome.attempts.len(),
+                            best_length: outcome.solution.map(|solution| solution.colors.len()),
+                        });
+                    }
+                }
+
+                let row = BenchRow::from_runs(
+                    bench_args.label(),
+                    name,
+                    &value_name(strategy),
+                    &value_name(encoding),
+                    &runs,
+                );
+                eprintln!(
+                    "{name} {} {}: median {:.3}s, {} z3 calls",
+                    row.strategy, row.encoding, row.median_secs, row.z3_calls
+                );
+                rows.push(row);
+            }
+        }
+    }
+
+    let result = match bench_args.output() {
+        Some(path) => std::fs::File::create(path)
+            .and_then(|mut file| write_table(&mut file, args.format(), &rows)),
+        None => write_table(&mut std::io::stdout().lock(), args.format(), &rows),
+    };
+    if let Err(err) = result {
+        eprintln!("Could not write results: {err}");
+        std::process::exit(1);
+    }
+}
+
+/// Name of a command line value as accepted by the parser
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_owned())
+        .expect("Values are never skipped")
+}
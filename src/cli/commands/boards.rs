@@ -0,0 +1,201 @@
+//! Generating, designing and rating boards
+
+#[cfg(feature = "z3")]
+use crate::{
+    cli::{input::instances_or_exit, DesignArgs, RateArgs},
+    generator::{design, Evaluation},
+    rating::{rate, Features},
+    report::{DesignReport, InstanceInfo, RatingReport},
+    solver::{self, count_solutions, SolveOptions},
+};
+use crate::{
+    cli::{Args, GenerateArgs, OutputFormat},
+    generator::{generate, GeneratorOptions},
+    report::BoardReport,
+};
+
+use super::print_json;
+
+/// Generates random instances and prints them or writes them to files
+pub(super) fn generate_instances(args: &Args, generate_args: &GenerateArgs) {
+    let options = generate_args.options();
+    validate_board(&options);
+
+    if let Some(out_dir) = generate_args.out_dir() {
+        if let Err(err) = std::fs::create_dir_all(out_dir) {
+            eprintln!("Could not create {}: {err}", out_dir.display());
+            std::process::exit(1);
+        }
+    }
+
+    for idx in 0..generate_args.count() {
+        let seed = options.seed.wrapping_add(idx as u64);
+        let instance = generate(&GeneratorOptions {
+            seed,
+            ..options.clone()
+        });
+
+        match generate_args.out_dir() {
+            Some(out_dir) => {
+                let path = out_dir.join(format!("{idx:03}.txt"));
+                if let Err(err) = std::fs::write(&path, instance.to_ascii()) {
+                    eprintln!("Could not write {}: {err}", path.display());
+                    std::process::exit(1);
+                }
+            }
+            None if args.format() == OutputFormat::Json => {
+                print_json(&BoardReport::new(&instance, Some(seed)));
+            }
+            None => {
+                if idx > 0 {
+                    println!();
+                }
+                print!("{}", instance.to_ascii());
+            }
+        }
+    }
+}
+
+/// Searches for a board with the requested optimal solution length and prints it
+#[cfg(feature = "z3")]
+pub(super) fn design_instance(args: &Args, design_args: &DesignArgs) {
+    let options = design_args.options();
+    validate_board(&options.board);
+
+    let mut evaluated = 0;
+    let found = design(&options, |candidate| {
+        evaluated += 1;
+
+        let solve_options = SolveOptions {
+            time_limit: design_args.time_limit(),
+            ..Default::default()
+        };
+        let outcome = solver::solve(candidate, &solve_options);
+        let optimum = match outcome.solution {
+            Some(solution) if outcome.optimal => solution.colors.len(),
+            _ => {
+                eprintln!("[{evaluated}] could not prove optimum, discarding board");
+                return None;
+            }
+        };
+
+        // only count solutions of boards which already have the requested length
+        let optimal_solutions = if options.unique && optimum == options.moves {
+            let mut cfg = z3::Config::new();
+            if let Some(time_limit) = design_args.time_limit() {
+                cfg.set_timeout_msec(time_limit.as_millis() as u64);
+            }
+            let ctx = z3::Context::new(&cfg);
+            match count_solutions::<z3::Solver>(&ctx, candidate, optimum, 2) {
+                (z3::SatResult::Sat, count) => Some(count),
+                _ => {
+                    eprintln!("[{evaluated}] could not count solutions, discarding board");
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+
+        match optimal_solutions {
+            Some(1) => eprintln!("[{evaluated}] optimum: {optimum}, unique"),
+            Some(_) => eprintln!("[{evaluated}] optimum: {optimum}, not unique"),
+            None => eprintln!("[{evaluated}] optimum: {optimum}"),
+        }
+
+        Some(Evaluation {
+            optimum,
+            optimal_solutions,
+        })
+    });
+
+    match found {
+        Some((instance, evaluation)) if args.format() == OutputFormat::Json => {
+            print_json(&DesignReport {
+                board: BoardReport::new(&instance, None),
+                optimum: evaluation.optimum,
+                optimal_solutions: evaluation.optimal_solutions,
+                evaluated,
+            })
+        }
+        Some((instance, _)) => print!("{}", instance.to_ascii()),
+        None => {
+            eprintln!(
+                "No board with an optimum of {} moves found within {} iterations",
+                options.moves, options.iterations
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Rates the difficulty of all instances given as file arguments or on stdin
+#[cfg(feature = "z3")]
+pub(super) fn rate_instances(args: &Args, rate_args: &RateArgs) {
+    let instances = instances_or_exit(args);
+
+    for (name, instance) in instances.iter() {
+        let options = SolveOptions {
+            time_limit: rate_args.time_limit(),
+            ..Default::default()
+        };
+        let outcome = solver::solve(instance, &options);
+        let optimum = match outcome.solution {
+            Some(solution) if outcome.optimal => solution.colors.len(),
+            _ => {
+                eprintln!("{name}: could not prove optimum, skipping");
+                continue;
+            }
+        };
+
+        let mut cfg = z3::Config::new();
+        if let Some(time_limit) = rate_args.time_limit() {
+            cfg.set_timeout_msec(time_limit.as_millis() as u64);
+        }
+        let ctx = z3::Context::new(&cfg);
+        // a partial count would overrate the uniqueness of the optimal solution
+        let optimal_solutions =
+            match count_solutions::<z3::Solver>(&ctx, instance, optimum, rate_args.max_solutions())
+            {
+                (z3::SatResult::Sat, count) => count,
+                _ => {
+                    eprintln!("{name}: could not count optimal solutions, skipping");
+                    continue;
+                }
+            };
+
+        let features = Features::from_problem(instance, optimum, optimal_solutions);
+        let rating = rate(&features);
+
+        if args.format() == OutputFormat::Json {
+            print_json(&RatingReport {
+                instance: InstanceInfo::new(name, instance),
+                features,
+                rating,
+            });
+            continue;
+        }
+
+        println!(
+            "{name}: {:.1} ({:?})\n  clusters: {}, diameter: {}, color balance: {:.2}, greedy: {}, optimum: {}, optimal solutions: {}{}",
+            rating.score,
+            rating.tier,
+            features.clusters,
+            features.diameter,
+            features.color_balance,
+            features.greedy_length,
+            features.optimum,
+            features.optimal_solutions,
+            if features.optimal_solutions >= rate_args.max_solutions() { "+" } else { "" },
+        );
+    }
+}
+
+/// Exits if the board's parameters can't be satisfied
+fn validate_board(options: &GeneratorOptions) {
+    if options.height == 0 || options.width == 0 || options.height * options.width < options.colors
+    {
+        eprintln!("Grid must have at least one tile per color");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,59 @@
+//! Handlers of the binary's subcommands
+
+mod analysis;
+#[cfg(feature = "z3")]
+mod batch;
+mod boards;
+#[cfg(feature = "z3")]
+mod play;
+mod render;
+#[cfg(feature = "z3")]
+mod serve;
+mod solve;
+
+use super::{Args, Command};
+
+/// Runs the subcommand given by the arguments
+pub fn run(args: &Args) {
+    match args.get_command() {
+        Command::Solve(action) => solve::solve_instances(args, action),
+        #[cfg(feature = "z3")]
+        Command::Batch(batch_args) => batch::solve_batch(args, batch_args),
+        #[cfg(feature = "z3")]
+        Command::Bench(bench_args) => batch::run_bench(args, bench_args),
+        Command::Generate(generate_args) => boards::generate_instances(args, generate_args),
+        #[cfg(feature = "z3")]
+        Command::Design(design_args) => boards::design_instance(args, design_args),
+        #[cfg(feature = "z3")]
+        Command::Rate(rate_args) => boards::rate_instances(args, rate_args),
+        Command::Replay(replay_args) => render::replay_solution(args, replay_args),
+        Command::Svg(svg_args) => render::render_svg(args, svg_args),
+        Command::Export(export_args) => render::export_images(args, export_args),
+        Command::Import(import_args) => render::import_image(args, import_args),
+        #[cfg(feature = "z3")]
+        Command::Play(play_args) => play::play_instance(args, play_args),
+        #[cfg(feature = "z3")]
+        Command::Analyze(analyze_args) => analysis::analyze_instances(args, analyze_args),
+        #[cfg(feature = "z3")]
+        Command::Serve(serve_args) => serve::serve_api(serve_args),
+        Command::Graph(graph_args) => analysis::print_graphs(args, graph_args),
+    }
+}
+
+/// Prints a value as single line JSON document
+fn print_json<T: serde::Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("Reports are always serializable")
+    );
+}
+
+/// Prints the heading of an instance, if there is more than one
+fn print_heading(idx: usize, count: usize, name: &str) {
+    if count > 1 {
+        if idx > 0 {
+            println!();
+        }
+        println!("=== Instance {}/{}: {} ===\n", idx + 1, count, name);
+    }
+}
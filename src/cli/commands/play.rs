@@ -0,0 +1,50 @@
+//! Playing instances interactively
+
+use crate::{
+    cli::{input::single_instance_or_exit, Args, PlayArgs},
+    play::{self, Hint, Target},
+    problem::Problem,
+    solver::{self, SolveOptions},
+};
+
+/// Lets the user play the single given instance in the terminal
+pub(super) fn play_instance(args: &Args, play_args: &PlayArgs) {
+    let instance = single_instance_or_exit(args).1;
+
+    let solve_min = |board: &Problem| {
+        let options = SolveOptions {
+            time_limit: play_args.time_limit(),
+            ..Default::default()
+        };
+        solver::solve(board, &options)
+    };
+
+    println!("Computing optimum...");
+    let outcome = solve_min(&instance);
+    let target = outcome.solution.map(|solution| Target {
+        moves: solution.colors.len(),
+        optimal: outcome.optimal,
+    });
+
+    let played = play::play(&instance, target, |board| {
+        let outcome = solve_min(board);
+        let solution = outcome.solution?;
+        Some(Hint {
+            color: *solution.colors.first()?,
+            remaining: solution.colors.len() - 1,
+        })
+    });
+
+    match played {
+        Ok(Some(moves)) => match target {
+            Some(target) if moves <= target.moves => println!("Well done!"),
+            Some(target) => println!("Try again with {} moves or less", target.moves),
+            None => {}
+        },
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("Terminal error: {err}");
+            std::process::exit(1);
+        }
+    }
+}
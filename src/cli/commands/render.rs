@@ -0,0 +1,150 @@
+//! Replaying solutions and converting boards from and to images
+
+use crate::{
+    cli::{
+        input::{load_single_instance, load_solution, or_exit},
+        Args, ExportArgs, ImportArgs, OutputFormat, ReplayArgs, SvgArgs,
+    },
+    importer::{self, Image},
+    printer,
+    problem::Problem,
+    raster,
+    report::{BoardReport, ReplayStepReport},
+    solution::Solution,
+    svg,
+};
+
+use super::print_json;
+
+/// Steps through a stored solution of the single given instance
+pub(super) fn replay_solution(args: &Args, replay_args: &ReplayArgs) {
+    let (instance, solution) = or_exit(load_single_instance(args).and_then(|(_, instance)| {
+        load_solution(replay_args.solution(), &instance).map(|solution| (instance, solution))
+    }));
+
+    let mut board = instance.clone();
+    if args.format() == OutputFormat::Json {
+        let grid = |board: &Problem| board.to_ascii().lines().map(str::to_owned).collect();
+        print_json(&ReplayStepReport {
+            step: 0,
+            color: None,
+            grid: grid(&board),
+        });
+        for (idx, color) in solution.colors.iter().enumerate() {
+            board.apply_color(*color);
+            print_json(&ReplayStepReport {
+                step: idx + 1,
+                color: Some(*color),
+                grid: grid(&board),
+            });
+        }
+    } else {
+        println!("{}", solution);
+        printer::print_solution(&instance, &solution);
+        for color in solution.colors.iter() {
+            board.apply_color(*color);
+        }
+    }
+
+    if !board.is_solved() {
+        eprintln!("Solution does not solve the instance");
+        std::process::exit(1);
+    }
+}
+
+/// Renders the single given instance and optionally a stored solution of it as SVG
+pub(super) fn render_svg(args: &Args, svg_args: &SvgArgs) {
+    let svg = load_single_instance(args).and_then(|(_, instance)| match svg_args.solution() {
+        Some(path) => load_solution(path, &instance)
+            .map(|solution| svg::render_solution(&instance, &solution, &svg_args.options())),
+        None => Ok(svg::render_board(&instance, &svg_args.options())),
+    });
+    let svg = or_exit(svg);
+
+    match svg_args.output() {
+        Some(path) => {
+            if let Err(err) = std::fs::write(path, svg) {
+                eprintln!("Could not write {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+        None => print!("{svg}"),
+    }
+}
+
+/// Exports the single given instance and optionally a stored solution of it as raster images
+pub(super) fn export_images(args: &Args, export_args: &ExportArgs) {
+    let frames = load_single_instance(args).and_then(|(_, instance)| {
+        // fail before rendering frames which could never be written
+        let tile = export_args.options().tile_size as usize;
+        let size = instance.width().max(instance.height()).saturating_mul(tile);
+        if export_args.gif().is_some() && size > u16::MAX as usize {
+            return Err(format!(
+                "GIF frames must not be larger than 65535 pixels, but a --tile-size of {tile} results in {size} pixels"
+            ));
+        }
+
+        let solution = match export_args.solution() {
+            Some(path) => load_solution(path, &instance)?,
+            None => Solution::from([]),
+        };
+        Ok(raster::render_frames(
+            &instance,
+            &solution,
+            &export_args.options(),
+        ))
+    });
+    let frames: Vec<raster::Frame> = or_exit(frames);
+
+    if let Some(path) = export_args.gif() {
+        let written = std::fs::File::create(path).and_then(|file| {
+            raster::write_gif(std::io::BufWriter::new(file), &frames, export_args.delay())
+        });
+        if let Err(err) = written {
+            eprintln!("Could not write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(dir) = export_args.png_dir() {
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            eprintln!("Could not create {}: {err}", dir.display());
+            std::process::exit(1);
+        }
+        for (step, frame) in frames.iter().enumerate() {
+            let path = dir.join(format!("step-{step:03}.png"));
+            let written = std::fs::File::create(&path)
+                .and_then(|file| raster::write_png(std::io::BufWriter::new(file), frame));
+            if let Err(err) = written {
+                eprintln!("Could not write {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Imports a board from an image and prints it
+pub(super) fn import_image(args: &Args, import_args: &ImportArgs) {
+    let path = import_args.image();
+    let instance = std::fs::File::open(path)
+        .map_err(|err| format!("Could not open {}: {err}", path.display()))
+        .and_then(|file| {
+            Image::from_reader(std::io::BufReader::new(file))
+                .and_then(|image| importer::import(&image, &import_args.options()))
+                .map_err(|err| format!("Could not import {}: {err}", path.display()))
+        });
+    let instance = or_exit(instance);
+
+    match import_args.output() {
+        Some(path) => {
+            if let Err(err) = std::fs::write(path, instance.to_ascii()) {
+                eprintln!("Could not write {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+        None if args.format() == OutputFormat::Json => {
+            print_json(&BoardReport::new(&instance, None));
+        }
+        None => print!("{}", instance.to_ascii()),
+    }
+}
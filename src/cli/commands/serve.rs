@@ -0,0 +1,43 @@
+//! Serving the HTTP JSON API
+
+use crate::{
+    cli::ServeArgs,
+    server,
+    solver::{self, SolveOptions, Strategy},
+};
+
+use super::solve::solve_report;
+
+/// Serves the HTTP JSON API until the server fails
+pub(super) fn serve_api(serve_args: &ServeArgs) {
+    eprintln!("Listening on http://{}", serve_args.listen());
+    let options = serve_args.options();
+    let log = |access: &server::Access| {
+        eprintln!("{} {} {}", access.method, access.url, access.status);
+    };
+    let served = server::serve(
+        serve_args.listen(),
+        &options,
+        |job| {
+            let strategy = match job.strategy {
+                server::Strategy::Min => Strategy::Min,
+                server::Strategy::Opt => Strategy::Opt { upper_bound: None },
+                server::Strategy::Solve => Strategy::Solve,
+            };
+            let options = SolveOptions {
+                strategy,
+                prefix: job.prefix.to_vec(),
+                time_limit: job.time_limit,
+                ..Default::default()
+            };
+            let outcome = solver::solve(job.instance, &options);
+            solve_report(job.name, job.instance, &options, Some(&outcome))
+        },
+        log,
+    );
+
+    if let Err(err) = served {
+        eprintln!("Could not serve on {}: {err}", serve_args.listen());
+        std::process::exit(1);
+    }
+}
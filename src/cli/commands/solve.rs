@@ -0,0 +1,337 @@
+//! The solving subcommands
+
+use std::time::Instant;
+
+use crate::{
+    cancel::CancelHandle,
+    cli::{
+        input::{instances_or_exit, validate_prefix},
+        Action, Args, Limits, OutputFormat, SolverOption,
+    },
+    printer,
+    problem::Problem,
+    report::{InstanceInfo, SolveReport},
+    search::{self, SearchOptions},
+    solution::{Solution, SolutionFile},
+};
+#[cfg(feature = "z3")]
+use crate::{
+    cli::progress::{ProgressBar, RunPrinter},
+    report::{result_name, AttemptReport, Bounds},
+    solver::{self, SolveOptions, SolveOutcome},
+};
+
+use super::{print_heading, print_json};
+
+/// Solves all instances given as file arguments or on stdin and prints the solutions
+pub(super) fn solve_instances(args: &Args, action: &Action) {
+    let instances = instances_or_exit(args);
+
+    /* TODO: IMPROVEMENTS
+        - calculate color-path length for furthest cluster
+            - use as lower bound
+    */
+
+    validate_prefix(args, &instances);
+
+    if args.save_solution().is_some() && instances.len() > 1 {
+        eprintln!("--save-solution requires a single problem instance");
+        std::process::exit(1);
+    }
+
+    match args.solver() {
+        #[cfg(feature = "z3")]
+        SolverOption::Z3 => z3_instances(args, action, &instances),
+        SolverOption::Search => search_instances(args, action.limits(), &instances),
+    }
+}
+
+/// Solves all instances with z3 as given by the action and prints the solutions
+#[cfg(feature = "z3")]
+fn z3_instances(args: &Args, action: &Action, instances: &[(String, Problem)]) {
+    let json = args.format() == OutputFormat::Json;
+    let options = SolveOptions {
+        strategy: action.strategy(),
+        encoding: args.encoding(),
+        prefix: args.prefix().to_vec(),
+        time_limit: action.limits().time_limit(),
+        cancel: Some(cancel_on_ctrl_c()),
+    };
+    let cancelled = || {
+        options
+            .cancel
+            .as_ref()
+            .is_some_and(CancelHandle::is_cancelled)
+    };
+
+    for (idx, (name, instance)) in instances.iter().enumerate() {
+        if cancelled() {
+            break;
+        }
+
+        if json {
+            if args.print_asserts() {
+                print_asserts(instance, &options, json);
+            }
+            let outcome = (!args.dry_run()).then(|| solve_observed(args, instance, &options));
+            print_json(&solve_report(name, instance, &options, outcome.as_ref()));
+            if let Some(outcome) = outcome {
+                save_solution(args, instance, outcome.solution.as_ref(), outcome.optimal);
+            }
+            continue;
+        }
+
+        print_heading(idx, instances.len(), name);
+        println!("{}", instance);
+
+        let played = instance.after(args.prefix());
+        if !args.prefix().is_empty() {
+            println!("After {}\n\n{}", Solution::from(args.prefix()), played);
+        }
+
+        let (lo, hi) = solver::solution_bounds(instance, &options);
+        println!(
+            "Size: {} x {}\nColors: {}\nStrategy: {:?}\nSolution bounds: [{},{}]\n",
+            instance.height(),
+            instance.width(),
+            instance.num_colors(),
+            options.strategy,
+            lo,
+            hi,
+        );
+
+        if args.print_asserts() {
+            print_asserts(instance, &options, json);
+        }
+        if args.dry_run() {
+            continue;
+        }
+
+        let outcome = solve_observed(args, instance, &options);
+        if cancelled() {
+            println!("Interrupted, the best solution found so far may not be minimal");
+        }
+        println!("{:?}", outcome.result);
+
+        if outcome.result == z3::SatResult::Sat {
+            if let Some(solution) = &outcome.solution {
+                println!("{}", solution);
+                printer::print_solution(&played, solution);
+            } else {
+                println!("Could not extract solution");
+            }
+        }
+
+        if args.stats() {
+            print_stats(&outcome);
+        }
+
+        save_solution(args, instance, outcome.solution.as_ref(), outcome.optimal);
+    }
+}
+
+/// Solves all instances by heuristic search without z3 and prints the solutions
+///
+/// Searches exceeding their limits or interrupted by Ctrl-C print the greedy solution instead.
+fn search_instances(args: &Args, limits: &Limits, instances: &[(String, Problem)]) {
+    if args.print_asserts() || args.dry_run() || args.progress_bar() || args.stats() {
+        eprintln!("--print-asserts, --dry-run, --progress-bar and --stats require --solver z3");
+        std::process::exit(1);
+    }
+
+    let cancel = cancel_on_ctrl_c();
+    for (idx, (name, instance)) in instances.iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        let played = instance.after(args.prefix());
+        let options = SearchOptions {
+            deadline: limits.time_limit().map(|limit| Instant::now() + limit),
+            max_expansions: limits.max_expansions(),
+            cancel: Some(cancel.clone()),
+        };
+        let outcome = search::search(&played, &options);
+
+        if args.format() == OutputFormat::Json {
+            print_json(&SolveReport {
+                instance: InstanceInfo::new(name, instance),
+                strategy: "heuristic-search",
+                prefix: args.prefix().to_vec(),
+                bounds: None,
+                attempts: vec![],
+                result: Some("sat"),
+                solution: Some(outcome.solution.colors.clone()),
+                optimal: outcome.optimal,
+            });
+        } else {
+            print_heading(idx, instances.len(), name);
+            println!("{}", instance);
+            if !args.prefix().is_empty() {
+                println!("After {}\n\n{}", Solution::from(args.prefix()), played);
+            }
+            println!(
+                "Size: {} x {}\nColors: {}\nSolver: search\n",
+                instance.height(),
+                instance.width(),
+                instance.num_colors(),
+            );
+
+            if cancel.is_cancelled() {
+                println!("Interrupted, printing the greedy solution");
+            }
+            println!("{}", outcome.solution);
+            printer::print_solution(&played, &outcome.solution);
+            println!(
+                "Expanded {} states, the solution is {}",
+                outcome.expanded,
+                if outcome.optimal {
+                    "minimal"
+                } else {
+                    "not necessarily minimal"
+                }
+            );
+        }
+
+        save_solution(args, instance, Some(&outcome.solution), outcome.optimal);
+    }
+}
+
+/// Cancels the returned handle on the first Ctrl-C, so the running search stops and reports the
+/// best solution found so far, and exits on the second one
+fn cancel_on_ctrl_c() -> CancelHandle {
+    let cancel = CancelHandle::new();
+    let handle = cancel.clone();
+    let installed = ctrlc::set_handler(move || {
+        if handle.is_cancelled() {
+            std::process::exit(130);
+        }
+        eprintln!("\nInterrupted, stopping the search. Press Ctrl-C again to quit immediately");
+        handle.cancel();
+    });
+    if let Err(err) = installed {
+        eprintln!("Could not handle Ctrl-C: {err}");
+    }
+    cancel
+}
+
+/// Prints the encoding size and timings of every z3 run of a search
+#[cfg(feature = "z3")]
+fn print_stats(outcome: &SolveOutcome) {
+    println!("\nStatistics of {} z3 runs:", outcome.checks());
+    println!(
+        "{:>6}  {:<7}  {:>8}  {:>10}  {:>10}",
+        "Size", "Result", "Asserts", "Encoding", "Check"
+    );
+    for attempt in outcome.attempts.iter() {
+        println!(
+            "{:>6}  {:<7}  {:>8}  {:>9.3}s  {:>9.3}s",
+            attempt.t,
+            format!("{:?}", attempt.result),
+            attempt.asserts,
+            attempt.encoding_time.as_secs_f64(),
+            attempt.time.as_secs_f64(),
+        );
+    }
+    println!(
+        "{:>6}  {:<7}  {:>8}  {:>9.3}s  {:>9.3}s",
+        "Total",
+        "",
+        "",
+        outcome.encoding_time().as_secs_f64(),
+        outcome.check_time().as_secs_f64(),
+    );
+}
+
+/// Solves an instance, showing the progress as configured by the arguments
+///
+/// Progress bars are drawn on stderr, whereas every z3 run is printed to stdout in text mode only.
+#[cfg(feature = "z3")]
+fn solve_observed(args: &Args, instance: &Problem, options: &SolveOptions) -> SolveOutcome {
+    if args.progress_bar() {
+        let mut progress_bar = ProgressBar::new();
+        let outcome = solver::solve_with(instance, options, &mut progress_bar);
+        progress_bar.finish();
+        outcome
+    } else if args.format() == OutputFormat::Text {
+        solver::solve_with(instance, options, &mut RunPrinter)
+    } else {
+        solver::solve(instance, options)
+    }
+}
+
+/// Prints the assertions of the first z3 run, to stderr to keep machine-readable output clean
+#[cfg(feature = "z3")]
+fn print_asserts(instance: &Problem, options: &SolveOptions, json: bool) {
+    let asserts = solver::initial_asserts(instance, options);
+    let lines = std::iter::once(format!("Got {} asserts:", asserts.len())).chain(asserts);
+    for line in lines {
+        if json {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+/// Writes the solution of an instance to the file given by `--save-solution`, if any
+fn save_solution(args: &Args, instance: &Problem, solution: Option<&Solution>, optimal: bool) {
+    let Some(path) = args.save_solution() else {
+        return;
+    };
+    let Some(solution) = solution else {
+        eprintln!("No solution found, not writing {}", path.display());
+        return;
+    };
+
+    // store the moves played along with their continuation, so the file replays on `instance`
+    let prefix = args.prefix();
+    let file = SolutionFile {
+        solution: Solution::from([prefix, &solution.colors].concat()),
+        instance: Some(instance.fingerprint()),
+        optimal: prefix.is_empty().then_some(optimal),
+    };
+    if let Err(err) = std::fs::write(path, file.to_string()) {
+        eprintln!("Could not write {}: {err}", path.display());
+        std::process::exit(1);
+    }
+}
+
+/// Builds the machine-readable report of solving an instance
+#[cfg(feature = "z3")]
+pub(super) fn solve_report(
+    name: &str,
+    instance: &Problem,
+    options: &SolveOptions,
+    outcome: Option<&SolveOutcome>,
+) -> SolveReport {
+    SolveReport {
+        instance: InstanceInfo::new(name, instance),
+        strategy: options.strategy.name(),
+        prefix: options.prefix.clone(),
+        bounds: outcome.map(|outcome| Bounds {
+            lower: outcome.bounds.0,
+            upper: outcome.bounds.1,
+        }),
+        attempts: outcome
+            .map(|outcome| {
+                outcome
+                    .attempts
+                    .iter()
+                    .map(|attempt| AttemptReport {
+                        t: attempt.t,
+                        result: result_name(attempt.result),
+                        time_secs: attempt.time.as_secs_f64(),
+                        encoding_secs: attempt.encoding_time.as_secs_f64(),
+                        asserts: attempt.asserts,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        result: outcome.map(|outcome| result_name(outcome.result)),
+        solution: outcome
+            .and_then(|outcome| outcome.solution.as_ref())
+            .map(|solution| solution.colors.clone()),
+        optimal: outcome.is_some_and(|outcome| outcome.optimal),
+    }
+}
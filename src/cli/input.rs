@@ -0,0 +1,135 @@
+//! Loading problem instances and solutions given on the command line
+
+use std::path::Path;
+
+use crate::{
+    problem::Problem,
+    solution::{Solution, SolutionFile},
+};
+
+use super::Args;
+
+/// Returns the loaded value, or prints the error and exits
+pub(super) fn or_exit<T>(loaded: Result<T, String>) -> T {
+    loaded.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    })
+}
+
+/// Loads all problem instances like [load_instances], or prints the error and exits
+pub(super) fn instances_or_exit(args: &Args) -> Vec<(String, Problem)> {
+    or_exit(load_instances(args))
+}
+
+/// Loads exactly one problem instance like [load_single_instance], or prints the error and exits
+#[cfg(feature = "z3")]
+pub(super) fn single_instance_or_exit(args: &Args) -> (String, Problem) {
+    or_exit(load_single_instance(args))
+}
+
+/// Exits if `--prefix` uses a color that does not occur in one of the instances
+pub(super) fn validate_prefix(args: &Args, instances: &[(String, Problem)]) {
+    if let Some((name, color)) = instances.iter().find_map(|(name, instance)| {
+        args.prefix()
+            .iter()
+            .find(|color| **color as usize >= instance.num_colors())
+            .map(|color| (name, color))
+    }) {
+        eprintln!("Prefix uses color {color}, which does not occur in {name}");
+        std::process::exit(1);
+    }
+}
+
+/// Loads all problem instances from the files given as arguments or from stdin
+///
+/// Every instance is returned along with a name denoting its origin.
+pub(super) fn load_instances(args: &Args) -> Result<Vec<(String, Problem)>, String> {
+    let mut instances = vec![];
+
+    if args.files().is_empty() {
+        // only load problem instances if stdin isn't a tty
+        if atty::is(atty::Stream::Stdin) {
+            return Err("No problem supplied on stdin or as file argument".to_owned());
+        }
+
+        let problems = Problem::read_all(std::io::stdin().lock())
+            .map_err(|err| format!("Invalid problem instance on stdin: {err}"))?;
+        instances.extend(name_instances("stdin", problems));
+    }
+
+    for path in args.files() {
+        instances.extend(load_file(path)?);
+    }
+
+    Ok(instances)
+}
+
+/// Loads exactly one problem instance from the files given as arguments or from stdin
+pub(super) fn load_single_instance(args: &Args) -> Result<(String, Problem), String> {
+    let mut instances = load_instances(args)?;
+    if instances.len() != 1 {
+        return Err(format!(
+            "Expected a single problem instance, found {}",
+            instances.len()
+        ));
+    }
+    Ok(instances.remove(0))
+}
+
+/// Loads a solution file and checks that it belongs to `instance`
+pub(super) fn load_solution(path: &Path, instance: &Problem) -> Result<Solution, String> {
+    let file = std::fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {}: {err}", path.display()))?
+        .parse::<SolutionFile>()
+        .map_err(|err| format!("Invalid solution in {}: {err}", path.display()))?;
+
+    if file
+        .instance
+        .is_some_and(|fingerprint| fingerprint != instance.fingerprint())
+    {
+        return Err(format!(
+            "{} belongs to a different problem instance",
+            path.display()
+        ));
+    }
+    if let Some(color) = file
+        .solution
+        .colors
+        .iter()
+        .find(|color| **color as usize >= instance.num_colors())
+    {
+        return Err(format!(
+            "Solution uses color {color}, but the instance only has {} colors",
+            instance.num_colors()
+        ));
+    }
+
+    Ok(file.solution)
+}
+
+/// Loads all problem instances from a single file
+pub(super) fn load_file(path: &Path) -> Result<Vec<(String, Problem)>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|err| format!("Could not open {}: {err}", path.display()))?;
+    let problems = Problem::read_all(std::io::BufReader::new(file))
+        .map_err(|err| format!("Invalid problem instance in {}: {err}", path.display()))?;
+    Ok(name_instances(&path.display().to_string(), problems))
+}
+
+/// Names the instances read from `source`, numbering them if there is more than one
+fn name_instances(source: &str, problems: Vec<Problem>) -> Vec<(String, Problem)> {
+    let count = problems.len();
+    problems
+        .into_iter()
+        .enumerate()
+        .map(|(idx, problem)| {
+            let name = if count > 1 {
+                format!("{source}#{}", idx + 1)
+            } else {
+                source.to_owned()
+            };
+            (name, problem)
+        })
+        .collect()
+}
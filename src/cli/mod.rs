@@ -0,0 +1,914 @@
+//! Command line interface: the argument parser and the handlers of all subcommands
+
+pub mod commands;
+mod input;
+#[cfg(feature = "z3")]
+mod progress;
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::*;
+
+use crate::generator::{DesignOptions, Distribution, GeneratorOptions};
+use crate::importer::ImportOptions;
+use crate::problem::Color;
+use crate::raster::RasterOptions;
+use crate::server::ServerOptions;
+#[cfg(feature = "z3")]
+use crate::solver::{Encoding, Strategy};
+use crate::svg::SvgOptions;
+
+/// Solver for the 'flood it' puzzle
+#[derive(clap::Parser)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+    #[arg(
+        global = true,
+        long = "print-asserts",
+        help = "Print asserts in SMT-LIB format"
+    )]
+    print_asserts: bool,
+    #[arg(
+        global = true,
+        long = "dry-run",
+        help = "Only create asserts but don't solve"
+    )]
+    dry_run: bool,
+    #[arg(
+        global = true,
+        long = "force-progress",
+        help = "Encode that every move must flood at least one more cluster (opt only)"
+    )]
+    force_progress: bool,
+    #[arg(
+        global = true,
+        long = "progress-bar",
+        help = "Show the progress of the search as bar on stderr instead of printing every z3 run"
+    )]
+    progress_bar: bool,
+    #[arg(
+        global = true,
+        long = "stats",
+        help = "Print the encoding size and timings of every z3 run after solving"
+    )]
+    stats: bool,
+    #[arg(
+        global = true,
+        long = "format",
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help = "Output format"
+    )]
+    format: OutputFormat,
+    #[arg(
+        global = true,
+        long = "solver",
+        value_enum,
+        default_value_t = SolverOption::default(),
+        help = "Solver of the solving subcommands"
+    )]
+    solver: SolverOption,
+    #[arg(
+        global = true,
+        long = "save-solution",
+        help = "Write the solution found to this file"
+    )]
+    save_solution: Option<PathBuf>,
+    #[arg(
+        global = true,
+        long = "prefix",
+        value_delimiter = ',',
+        help = "Moves already played, e.g. 0,2,1. Finds the best continuation"
+    )]
+    prefix: Vec<Color>,
+    #[arg(
+        global = true,
+        help = "Files containing problem instances. Reads from stdin if omitted"
+    )]
+    files: Vec<PathBuf>,
+}
+
+impl Args {
+    pub fn get_command(&self) -> &Command {
+        &self.command
+    }
+
+    pub fn print_asserts(&self) -> bool {
+        self.print_asserts
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    #[cfg(feature = "z3")]
+    pub fn encoding(&self) -> Encoding {
+        Encoding {
+            force_progress: self.force_progress,
+        }
+    }
+
+    pub fn progress_bar(&self) -> bool {
+        self.progress_bar
+    }
+
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn solver(&self) -> SolverOption {
+        self.solver
+    }
+
+    pub fn save_solution(&self) -> Option<&Path> {
+        self.save_solution.as_deref()
+    }
+
+    pub fn prefix(&self) -> &[Color] {
+        &self.prefix
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+/// Subcommands of the binary
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Solve(Action),
+    #[cfg(feature = "z3")]
+    #[command(about = "Solve every instance in a directory and print a results table")]
+    Batch(BatchArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Benchmark strategies and encodings on the given instances")]
+    Bench(BenchArgs),
+    #[command(about = "Generate random problem instances")]
+    Generate(GenerateArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Search for a board with a given optimal solution length")]
+    Design(DesignArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Rate the difficulty of the given instances")]
+    Rate(RateArgs),
+    #[command(about = "Step through a stored solution of the given instance")]
+    Replay(ReplayArgs),
+    #[command(about = "Render the given instance or a stored solution of it as SVG")]
+    Svg(SvgArgs),
+    #[command(about = "Export the given instance or a stored solution of it as PNG frames or GIF")]
+    Export(ExportArgs),
+    #[command(about = "Import a board from a PNG or PPM image")]
+    Import(ImportArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Play the given instance interactively")]
+    Play(PlayArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Evaluate every possible next move of the given instances")]
+    Analyze(AnalyzeArgs),
+    #[cfg(feature = "z3")]
+    #[command(about = "Serve solve, verify, analyze and generate over a local HTTP JSON API")]
+    Serve(ServeArgs),
+    #[command(about = "Print the cluster adjacency graph of the given instances")]
+    Graph(GraphArgs),
+}
+
+/// Arguments of the `batch` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct BatchArgs {
+    #[arg(help = "Directory containing problem instances")]
+    dir: PathBuf,
+    #[arg(
+        long = "time-limit",
+        help = "Time limit per instance in seconds. Reports the best solution found so far"
+    )]
+    time_limit: Option<u64>,
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        help = "Number of instances solved in parallel [default: number of CPU cores]"
+    )]
+    jobs: Option<usize>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Write table to file instead of stdout"
+    )]
+    output: Option<PathBuf>,
+    #[command(subcommand)]
+    strategy: Action,
+}
+
+impl BatchArgs {
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+
+    pub fn jobs(&self) -> usize {
+        self.jobs
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+    }
+
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    pub fn strategy(&self) -> &Action {
+        &self.strategy
+    }
+}
+
+/// Arguments of the `bench` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct BenchArgs {
+    #[arg(
+        long = "strategies",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "solve,min,opt",
+        help = "Strategies to benchmark"
+    )]
+    strategies: Vec<BenchStrategy>,
+    #[arg(
+        long = "encodings",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "default,force-progress",
+        help = "Encodings to benchmark"
+    )]
+    encodings: Vec<EncodingOption>,
+    #[arg(
+        short = 'r',
+        long = "repetitions",
+        default_value_t = 3,
+        help = "Number of runs per seed"
+    )]
+    repetitions: usize,
+    #[arg(
+        long = "seeds",
+        value_delimiter = ',',
+        default_value = "0",
+        help = "z3 random seeds to run with"
+    )]
+    seeds: Vec<u32>,
+    #[arg(long = "time-limit", help = "Time limit per run in seconds")]
+    time_limit: Option<u64>,
+    #[arg(
+        long = "label",
+        default_value = "",
+        help = "Label added to every row, e.g. a commit hash, to compare tables"
+    )]
+    label: String,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Write table to file instead of stdout"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl BenchArgs {
+    pub fn strategies(&self) -> &[BenchStrategy] {
+        &self.strategies
+    }
+
+    pub fn encodings(&self) -> &[EncodingOption] {
+        &self.encodings
+    }
+
+    pub fn repetitions(&self) -> usize {
+        self.repetitions
+    }
+
+    pub fn seeds(&self) -> &[u32] {
+        &self.seeds
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+}
+
+/// Strategies that can be benchmarked, i.e. [Actions](Action) without arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchStrategy {
+    Opt,
+    Min,
+    Solve,
+}
+
+impl BenchStrategy {
+    #[cfg(feature = "z3")]
+    pub fn to_strategy(self) -> Strategy {
+        match self {
+            BenchStrategy::Opt => Strategy::Opt { upper_bound: None },
+            BenchStrategy::Min => Strategy::Min,
+            BenchStrategy::Solve => Strategy::Solve,
+        }
+    }
+}
+
+/// Named [Encoding] variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EncodingOption {
+    Default,
+    ForceProgress,
+}
+
+impl EncodingOption {
+    #[cfg(feature = "z3")]
+    pub fn to_encoding(self) -> Encoding {
+        match self {
+            EncodingOption::Default => Encoding::default(),
+            EncodingOption::ForceProgress => Encoding {
+                force_progress: true,
+            },
+        }
+    }
+}
+
+/// Parameters of randomly generated boards
+#[derive(Debug, Clone, clap::Args)]
+pub struct BoardArgs {
+    #[arg(long = "height", help = "Number of rows")]
+    height: usize,
+    #[arg(long = "width", help = "Number of columns [default: height]")]
+    width: Option<usize>,
+    #[arg(
+        long = "colors",
+        value_parser = value_parser!(u8).range(1..=10),
+        help = "Number of colors"
+    )]
+    colors: u8,
+    #[arg(
+        long = "seed",
+        default_value_t = 0,
+        help = "Seed of the first instance"
+    )]
+    seed: u64,
+    #[arg(long = "distribution", value_enum, default_value_t = DistributionOption::Uniform)]
+    distribution: DistributionOption,
+    #[arg(
+        long = "cohesion",
+        default_value_t = 0.5,
+        help = "Probability of a tile copying a neighbour's color (clustered only)"
+    )]
+    cohesion: f64,
+}
+
+impl BoardArgs {
+    pub fn options(&self) -> GeneratorOptions {
+        GeneratorOptions {
+            height: self.height,
+            width: self.width.unwrap_or(self.height),
+            colors: self.colors as usize,
+            seed: self.seed,
+            distribution: match self.distribution {
+                DistributionOption::Uniform => Distribution::Uniform,
+                DistributionOption::Clustered => Distribution::Clustered {
+                    cohesion: self.cohesion,
+                },
+            },
+        }
+    }
+}
+
+/// Arguments of the `generate` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+    #[arg(
+        short = 'n',
+        long = "count",
+        default_value_t = 1,
+        help = "Number of instances, generated with consecutive seeds"
+    )]
+    count: usize,
+    #[arg(
+        long = "out-dir",
+        help = "Write every instance to its own file in this directory instead of stdout"
+    )]
+    out_dir: Option<PathBuf>,
+}
+
+impl GenerateArgs {
+    pub fn options(&self) -> GeneratorOptions {
+        self.board.options()
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn out_dir(&self) -> Option<&Path> {
+        self.out_dir.as_deref()
+    }
+}
+
+/// Arguments of the `design` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct DesignArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+    #[arg(long = "moves", help = "Requested optimal solution length")]
+    moves: usize,
+    #[arg(long = "unique", help = "Require exactly one optimal color sequence")]
+    unique: bool,
+    #[arg(
+        long = "iterations",
+        default_value_t = 500,
+        help = "Max number of mutations to try"
+    )]
+    iterations: usize,
+    #[arg(
+        long = "time-limit",
+        help = "Time limit per evaluated board in seconds. Boards exceeding it are discarded"
+    )]
+    time_limit: Option<u64>,
+}
+
+impl DesignArgs {
+    pub fn options(&self) -> DesignOptions {
+        DesignOptions {
+            board: self.board.options(),
+            moves: self.moves,
+            unique: self.unique,
+            iterations: self.iterations,
+        }
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+}
+
+/// Arguments of the `rate` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct RateArgs {
+    #[arg(
+        long = "max-solutions",
+        default_value_t = 10,
+        help = "Stop counting optimal solutions at this number"
+    )]
+    max_solutions: usize,
+    #[arg(long = "time-limit", help = "Time limit per instance in seconds")]
+    time_limit: Option<u64>,
+}
+
+impl RateArgs {
+    pub fn max_solutions(&self) -> usize {
+        self.max_solutions
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+}
+
+/// Arguments of the `replay` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReplayArgs {
+    #[arg(help = "Solution file, as written by --save-solution")]
+    solution: PathBuf,
+}
+
+impl ReplayArgs {
+    pub fn solution(&self) -> &Path {
+        &self.solution
+    }
+}
+
+/// Arguments of the `svg` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct SvgArgs {
+    #[arg(
+        long = "solution",
+        help = "Solution file whose moves are drawn step by step"
+    )]
+    solution: Option<PathBuf>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Write image to file instead of stdout"
+    )]
+    output: Option<PathBuf>,
+    #[arg(
+        long = "tile-size",
+        default_value_t = 24,
+        value_parser = value_parser!(u32).range(1..),
+        help = "Edge length of a tile in pixels"
+    )]
+    tile_size: u32,
+    #[arg(
+        long = "columns",
+        default_value_t = 4,
+        help = "Number of boards per row"
+    )]
+    columns: usize,
+    #[arg(long = "no-outlines", help = "Don't outline every tile")]
+    no_outlines: bool,
+    #[arg(long = "no-highlight", help = "Don't highlight the flooded region")]
+    no_highlight: bool,
+    #[arg(
+        long = "no-move-numbers",
+        help = "Don't label boards with their move number"
+    )]
+    no_move_numbers: bool,
+}
+
+impl SvgArgs {
+    pub fn solution(&self) -> Option<&Path> {
+        self.solution.as_deref()
+    }
+
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    pub fn options(&self) -> SvgOptions {
+        SvgOptions {
+            tile_size: self.tile_size,
+            outlines: !self.no_outlines,
+            highlight_flooded: !self.no_highlight,
+            move_numbers: !self.no_move_numbers,
+            columns: self.columns,
+        }
+    }
+}
+
+/// Arguments of the `export` subcommand
+#[derive(Debug, Clone, clap::Args)]
+#[command(group(ArgGroup::new("target").required(true).multiple(true).args(["gif", "png_dir"])))]
+pub struct ExportArgs {
+    #[arg(
+        long = "solution",
+        help = "Solution file whose moves are exported step by step"
+    )]
+    solution: Option<PathBuf>,
+    #[arg(long = "gif", help = "Write an animated GIF to this file")]
+    gif: Option<PathBuf>,
+    #[arg(
+        long = "png-dir",
+        help = "Write one PNG image per step to this directory"
+    )]
+    png_dir: Option<PathBuf>,
+    #[arg(
+        long = "tile-size",
+        default_value_t = 24,
+        value_parser = value_parser!(u32).range(1..),
+        help = "Edge length of a tile in pixels"
+    )]
+    tile_size: u32,
+    #[arg(
+        long = "delay",
+        default_value_t = 500,
+        help = "Time every GIF frame is shown in milliseconds"
+    )]
+    delay: u64,
+    #[arg(long = "no-outlines", help = "Don't outline every tile")]
+    no_outlines: bool,
+    #[arg(long = "no-highlight", help = "Don't highlight the flooded region")]
+    no_highlight: bool,
+}
+
+impl ExportArgs {
+    pub fn solution(&self) -> Option<&Path> {
+        self.solution.as_deref()
+    }
+
+    pub fn gif(&self) -> Option<&Path> {
+        self.gif.as_deref()
+    }
+
+    pub fn png_dir(&self) -> Option<&Path> {
+        self.png_dir.as_deref()
+    }
+
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay)
+    }
+
+    pub fn options(&self) -> RasterOptions {
+        RasterOptions {
+            tile_size: self.tile_size,
+            outlines: !self.no_outlines,
+            highlight_flooded: !self.no_highlight,
+        }
+    }
+}
+
+/// Arguments of the `import` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct ImportArgs {
+    #[arg(help = "PNG or PPM image, cropped to the board")]
+    image: PathBuf,
+    #[arg(
+        long = "rows",
+        requires = "columns",
+        help = "Number of rows [default: detected]"
+    )]
+    rows: Option<usize>,
+    #[arg(
+        long = "columns",
+        requires = "rows",
+        help = "Number of columns [default: detected]"
+    )]
+    columns: Option<usize>,
+    #[arg(
+        long = "colors",
+        value_parser = value_parser!(u8).range(1..=10),
+        help = "Number of colors [default: derived from tolerance]"
+    )]
+    colors: Option<u8>,
+    #[arg(
+        long = "tolerance",
+        default_value_t = 48.0,
+        help = "Max RGB distance of tiles of the same color"
+    )]
+    tolerance: f64,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Write instance to file instead of stdout"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl ImportArgs {
+    pub fn image(&self) -> &Path {
+        &self.image
+    }
+
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    pub fn options(&self) -> ImportOptions {
+        ImportOptions {
+            grid: self.rows.zip(self.columns),
+            colors: self.colors.map(|colors| colors as usize),
+            tolerance: self.tolerance,
+        }
+    }
+}
+
+/// Arguments of the `play` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct PlayArgs {
+    #[arg(
+        long = "time-limit",
+        help = "Time limit for computing the optimum and every hint in seconds"
+    )]
+    time_limit: Option<u64>,
+}
+
+impl PlayArgs {
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+}
+
+/// Arguments of the `analyze` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct AnalyzeArgs {
+    #[arg(long = "time-limit", help = "Time limit per evaluated move in seconds")]
+    time_limit: Option<u64>,
+}
+
+impl AnalyzeArgs {
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+}
+
+/// Arguments of the `graph` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct GraphArgs {
+    #[arg(
+        long = "dot",
+        help = "Print the graphs in the DOT language of GraphViz"
+    )]
+    dot: bool,
+    #[arg(
+        long = "diameter",
+        help = "Also compute the diameter of every graph, which is slow for large graphs"
+    )]
+    diameter: bool,
+}
+
+impl GraphArgs {
+    pub fn dot(&self) -> bool {
+        self.dot
+    }
+
+    pub fn diameter(&self) -> bool {
+        self.diameter
+    }
+}
+
+/// Arguments of the `serve` subcommand
+#[derive(Debug, Clone, clap::Args)]
+pub struct ServeArgs {
+    #[arg(
+        long = "listen",
+        default_value = "127.0.0.1:7878",
+        help = "Address to listen on"
+    )]
+    listen: String,
+    #[arg(
+        long = "workers",
+        default_value_t = 1,
+        value_parser = value_parser!(u32).range(1..),
+        help = "Number of requests handled in parallel"
+    )]
+    workers: u32,
+    #[arg(
+        long = "queue-size",
+        default_value_t = 16,
+        help = "Number of requests waiting for a worker before further requests are rejected"
+    )]
+    queue_size: usize,
+    #[arg(
+        long = "time-limit",
+        help = "Time limit in seconds of requests that don't specify one"
+    )]
+    time_limit: Option<u64>,
+    #[arg(
+        long = "max-time-limit",
+        help = "Upper bound in seconds of the time limit requests may specify"
+    )]
+    max_time_limit: Option<u64>,
+    #[arg(
+        long = "max-tiles",
+        default_value_t = ServerOptions::default().max_tiles,
+        help = "Max number of tiles of boards given or generated by requests"
+    )]
+    max_tiles: usize,
+}
+
+impl ServeArgs {
+    pub fn listen(&self) -> &str {
+        &self.listen
+    }
+
+    pub fn options(&self) -> ServerOptions {
+        ServerOptions {
+            workers: self.workers as usize,
+            queue_size: self.queue_size,
+            default_time_limit: self.time_limit.map(Duration::from_secs),
+            max_time_limit: self.max_time_limit.map(Duration::from_secs),
+            max_tiles: self.max_tiles,
+        }
+    }
+}
+
+/// Named [Distribution] variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DistributionOption {
+    Uniform,
+    Clustered,
+}
+
+/// Solvers of the solving subcommands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SolverOption {
+    /// Search for solutions with z3 as given by the subcommand
+    #[cfg(feature = "z3")]
+    Z3,
+    /// Find a minimal solution by heuristic search without z3. Ignores the subcommand's bounds
+    Search,
+}
+
+impl Default for SolverOption {
+    /// z3 if the binary is built with it
+    fn default() -> Self {
+        #[cfg(feature = "z3")]
+        return SolverOption::Z3;
+        #[cfg(not(feature = "z3"))]
+        return SolverOption::Search;
+    }
+}
+
+/// Output format of all subcommands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable output. Tables are written as CSV
+    #[value(alias = "csv")]
+    Text,
+    /// One JSON document per line
+    Json,
+}
+
+/// Mode of finding an (optimal) solution
+#[derive(Debug, Clone, Subcommand)]
+pub enum Action {
+    #[command(about = "Use z3 optimizer to find minimal solution")]
+    Opt {
+        #[arg(long = "upper-bound", help = "Upper solution size bound")]
+        upper_bound: Option<usize>,
+        #[command(flatten)]
+        limits: Limits,
+    },
+    #[command(about = "Find minimal solution by binary search in reasonable bounds")]
+    Min {
+        #[command(flatten)]
+        limits: Limits,
+    },
+    #[command(about = "Find minimal solution by binary search in bounds")]
+    Search {
+        lower_bound: usize,
+        upper_bound: usize,
+        #[command(flatten)]
+        limits: Limits,
+    },
+    #[command(about = "Find solution with exact size")]
+    Exact {
+        size: usize,
+        #[command(flatten)]
+        limits: Limits,
+    },
+    #[command(about = "Find solution with reasonable large size")]
+    Solve {
+        #[command(flatten)]
+        limits: Limits,
+    },
+}
+
+/// Limits of the solving subcommands
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Limits {
+    #[arg(
+        long = "time-limit",
+        help = "Time limit per instance in seconds, after which the best solution found so far is printed"
+    )]
+    time_limit: Option<u64>,
+    #[arg(
+        long = "max-expansions",
+        help = "Max number of states expanded by --solver search per instance"
+    )]
+    max_expansions: Option<usize>,
+}
+
+impl Limits {
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
+
+    pub fn max_expansions(&self) -> Option<usize> {
+        self.max_expansions
+    }
+}
+
+impl Action {
+    pub fn limits(&self) -> &Limits {
+        match self {
+            Action::Opt { limits, .. }
+            | Action::Min { limits }
+            | Action::Search { limits, .. }
+            | Action::Exact { limits, .. }
+            | Action::Solve { limits } => limits,
+        }
+    }
+
+    /// The library's equivalent of this action
+    #[cfg(feature = "z3")]
+    pub fn strategy(&self) -> Strategy {
+        match *self {
+            Action::Opt { upper_bound, .. } => Strategy::Opt { upper_bound },
+            Action::Min { .. } => Strategy::Min,
+            Action::Search {
+                lower_bound,
+                upper_bound,
+                ..
+            } => Strategy::Search {
+                lower_bound,
+                upper_bound,
+            },
+            Action::Exact { size, .. } => Strategy::Exact { size },
+            Action::Solve { .. } => Strategy::Solve,
+        }
+    }
+}
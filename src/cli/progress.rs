@@ -0,0 +1,114 @@
+//! Showing the progress of searches
+
+use std::time::Instant;
+
+use crate::{
+    solution::Solution,
+    solver::{Attempt, Observer},
+};
+
+/// Prints every z3 run of a search when it starts
+pub(super) struct RunPrinter;
+
+impl Observer for RunPrinter {
+    fn check_started(&mut self, t: usize) {
+        println!("Starting z3 with size {t}...");
+    }
+}
+
+/// Draws the progress of a search as a single line on stderr, which is redrawn on every event
+pub(super) struct ProgressBar {
+    start: Instant,
+    /// Bounds the search started with
+    initial: Option<(usize, usize)>,
+    bounds: (usize, usize),
+    /// Length of the shortest solution found
+    best: Option<usize>,
+    /// Solution length and number of assertions of the running z3 check
+    running: Option<(usize, usize)>,
+    asserts: usize,
+    finished: bool,
+}
+
+impl ProgressBar {
+    const WIDTH: usize = 30;
+
+    pub(super) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            initial: None,
+            bounds: (0, 0),
+            best: None,
+            running: None,
+            asserts: 0,
+            finished: false,
+        }
+    }
+
+    fn draw(&self) {
+        // share of the initial bounds that was ruled out
+        let done = match self.initial {
+            _ if self.finished => 1.0,
+            Some((lo, hi)) => {
+                // bounds are never crossed, saturate anyway rather than underflow on bad input
+                let remaining = (self.bounds.1 + 1).saturating_sub(self.bounds.0);
+                let initial = (hi + 1).saturating_sub(lo).max(1);
+                1.0 - remaining as f64 / initial as f64
+            }
+            None => 0.0,
+        };
+        let filled = (done * Self::WIDTH as f64).round() as usize;
+
+        let mut line = format!(
+            "[{}{}] {:>3.0}% bounds [{},{}]",
+            "#".repeat(filled),
+            ".".repeat(Self::WIDTH - filled),
+            done * 100.0,
+            self.bounds.0,
+            self.bounds.1,
+        );
+        if let Some(best) = self.best {
+            line.push_str(&format!(", best {best}"));
+        }
+        if let Some((t, asserts)) = self.running {
+            line.push_str(&format!(", z3 with size {t} ({asserts} asserts)"));
+        }
+        line.push_str(&format!(", {:.1}s", self.start.elapsed().as_secs_f64()));
+
+        // return to the line's start and clear what's left of the previous line
+        eprint!("\r{line}\x1b[K");
+    }
+
+    pub(super) fn finish(&mut self) {
+        self.finished = true;
+        self.draw();
+        eprintln!();
+    }
+}
+
+impl Observer for ProgressBar {
+    fn encoded(&mut self, _t: usize, asserts: usize) {
+        self.asserts = asserts;
+    }
+
+    fn check_started(&mut self, t: usize) {
+        self.running = Some((t, self.asserts));
+        self.draw();
+    }
+
+    fn check_finished(&mut self, _attempt: &Attempt) {
+        self.running = None;
+        self.draw();
+    }
+
+    fn bounds_updated(&mut self, lo: usize, hi: usize) {
+        self.initial.get_or_insert((lo, hi));
+        self.bounds = (lo, hi);
+        self.draw();
+    }
+
+    fn solution_found(&mut self, solution: &Solution) {
+        self.best = Some(solution.colors.len());
+        self.draw();
+    }
+}
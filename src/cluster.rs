@@ -9,77 +9,238 @@ use crate::util::{neighbours, Point};
 #[derive(Debug)]
 pub struct Cluster {
     pub color: Color,
-    pub fields: HashSet<Point>,
+    /// The cluster's points, sorted row by row
+    pub fields: Vec<Point>,
 }
 
 impl Cluster {
     /// Extract a list of clusters from the problem's grid
     pub fn from_problem(instance: &Problem) -> Vec<Cluster> {
+        Clustering::from_problem(instance).clusters
+    }
+
+    /// If a point belongs to this cluster
+    pub fn contains(&self, point: Point) -> bool {
+        self.fields.binary_search(&point).is_ok()
+    }
+
+    /// Returns all points adjacent to this cluster
+    pub fn neighbours(&self, height: usize, width: usize) -> impl Iterator<Item = Point> + '_ {
+        self.fields
+            .iter()
+            .flat_map(move |(y, x)| neighbours(*y, *x, height, width))
+            .filter(|pos| !self.contains(*pos))
+    }
+
+    /// Returns ids (index in `clusters`) of all clusters adjacent to this cluster
+    ///
+    /// `clustering` must be the clustering this cluster was taken from.
+    pub fn neighbour_clusters(&self, clustering: &Clustering) -> HashSet<usize> {
+        let id = clustering.cluster_id(self.fields[0]);
+        clustering.neighbours(id).iter().copied().collect()
+    }
+}
+
+/// The clusters of a problem instance along with the cluster of every field and the adjacency of
+/// clusters, found in a single pass over the grid
+///
+/// Clusters are identified by their index in `clusters`, which is ordered by the first field of
+/// each cluster row by row.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{cluster::Clustering, problem::Problem};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let clustering = Clustering::from_problem(&instance);
+/// assert_eq!(clustering.len(), 7);
+/// assert_eq!(clustering.cluster_id((1, 0)), 3);
+/// assert_eq!(clustering.cluster_id((1, 1)), 2);
+/// assert_eq!(clustering.neighbours(0), &[1, 3]);
+/// assert_eq!(clustering.neighbours(2), &[1, 3, 5, 6]);
+/// assert_eq!(clustering.clusters[2].fields, vec![(0, 2), (1, 1), (1, 2)]);
+/// assert!(clustering.clusters[2].neighbour_clusters(&clustering).contains(&5));
+///
+/// // boards may be larger than 255x255 tiles
+/// let row = format!("{}{}", "0".repeat(150), "1".repeat(150));
+/// let instance: Problem = vec![row; 300].join("\n").parse().unwrap();
+/// let clustering = Clustering::from_problem(&instance);
+/// assert_eq!(clustering.len(), 2);
+/// assert_eq!(clustering.cluster_id((299, 149)), 0);
+/// assert_eq!(clustering.cluster_id((0, 150)), 1);
+/// assert_eq!(clustering.cluster_id((299, 299)), 1);
+/// assert_eq!(clustering.clusters[1].fields.len(), 150 * 300);
+/// assert_eq!(clustering.neighbours(1), &[0]);
+/// ```
+#[derive(Debug)]
+pub struct Clustering {
+    pub clusters: Vec<Cluster>,
+    height: usize,
+    width: usize,
+    /// Cluster id of every field, row by row
+    ids: Vec<usize>,
+    /// The clusters adjacent to cluster `i` are `adjacency[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    adjacency: Vec<usize>,
+}
+
+impl Clustering {
+    pub fn from_problem(instance: &Problem) -> Self {
         let Problem { grid } = instance;
         let height = instance.height();
         let width = instance.width();
 
         let mut clusters: Vec<Cluster> = vec![];
-        let mut visited: HashSet<Point> = Default::default();
+        let mut ids = vec![usize::MAX; height * width];
+        // every pair of adjacent clusters is found once, from the cluster found later
+        let mut edges: Vec<(usize, usize)> = vec![];
 
         for y in 0..height {
             for x in 0..width {
-                if !visited.insert((y as u8, x as u8)) {
+                if ids[y * width + x] != usize::MAX {
                     continue;
                 }
 
+                let id = clusters.len();
                 let color = grid[y][x];
+                let mut fields: Vec<Point> = vec![];
+                let mut queue: Vec<Point> = vec![(y, x)];
+                ids[y * width + x] = id;
 
-                let mut fields: HashSet<Point> = Default::default();
-                let mut queue: Vec<Point> = vec![(y as u8, x as u8)];
                 while let Some((y, x)) = queue.pop() {
-                    if fields.contains(&(y, x)) {
-                        continue;
-                    }
-
-                    if color == grid[y as usize][x as usize] {
-                        fields.insert((y, x));
-                        visited.insert((y, x));
+                    fields.push((y, x));
 
-                        for pos in neighbours(y, x, height, width) {
-                            queue.push(pos);
+                    for (ny, nx) in neighbours(y, x, height, width) {
+                        let neighbour = ny * width + nx;
+                        if grid[ny][nx] == color {
+                            if ids[neighbour] == usize::MAX {
+                                ids[neighbour] = id;
+                                queue.push((ny, nx));
+                            }
+                        } else if ids[neighbour] != usize::MAX {
+                            edges.push((id, ids[neighbour]));
+                            edges.push((ids[neighbour], id));
                         }
                     }
                 }
 
-                if !fields.is_empty() {
-                    clusters.push(Cluster { color, fields });
-                }
+                fields.sort_unstable();
+                clusters.push(Cluster { color, fields });
             }
         }
 
-        clusters
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut offsets = vec![0; clusters.len() + 1];
+        for (from, _) in edges.iter() {
+            offsets[from + 1] += 1;
+        }
+        for idx in 1..offsets.len() {
+            offsets[idx] += offsets[idx - 1];
+        }
+
+        Self {
+            clusters,
+            height,
+            width,
+            ids,
+            offsets,
+            adjacency: edges.into_iter().map(|(_, to)| to).collect(),
+        }
     }
-    /// Returns all points adjacent to this cluster
-    pub fn neighbours(&self, height: usize, width: usize) -> impl Iterator<Item = Point> + '_ {
-        self.fields
+
+    /// Number of clusters
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+
+    /// Height and width of the board
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    /// Id of the cluster containing a field
+    pub fn cluster_id(&self, (y, x): Point) -> usize {
+        self.ids[y * self.width + x]
+    }
+
+    /// Ids of all clusters adjacent to a cluster, in ascending order
+    pub fn neighbours(&self, id: usize) -> &[usize] {
+        &self.adjacency[self.offsets[id]..self.offsets[id + 1]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{generate, Distribution, GeneratorOptions};
+
+    /// A 500x500 checkerboard of 10x10 blocks, which only touch diagonally within a color
+    fn blocks() -> Problem {
+        let grid = (0..500)
+            .map(|y| (0..500).map(|x| ((y / 10 + x / 10) % 2) as Color).collect())
+            .collect();
+        Problem { grid }
+    }
+
+    #[test]
+    fn clusters_of_a_500x500_checkerboard() {
+        let instance = blocks();
+        let clustering = Clustering::from_problem(&instance);
+        assert_eq!(clustering.len(), 50 * 50);
+        assert!(clustering.clusters.iter().all(|c| c.fields.len() == 100));
+
+        // clusters are numbered by their first tile row by row
+        assert_eq!(clustering.cluster_id((9, 9)), 0);
+        assert_eq!(clustering.cluster_id((0, 10)), 1);
+        assert_eq!(clustering.cluster_id((10, 0)), 50);
+        assert_eq!(clustering.cluster_id((499, 499)), 2499);
+        assert_eq!(clustering.neighbours(0), &[1, 50]);
+        assert_eq!(clustering.neighbours(51), &[1, 50, 52, 101]);
+        assert_eq!(clustering.neighbours(2499), &[2449, 2498]);
+
+        let region = instance.flooded_region();
+        assert_eq!(region.len(), 100);
+        assert!(region.contains(&(9, 9)));
+        assert!(!region.contains(&(10, 10)));
+        assert!(clustering.clusters[0]
+            .fields
             .iter()
-            .flat_map(move |(y, x)| neighbours(*y, *x, height, width))
-            .filter(|pos| !self.fields.contains(pos))
+            .all(|p| region.contains(p)));
     }
 
-    /// Returns ids (index in `clusters`) of all clusters adjacent to this cluster
-    pub fn neighbour_clusters(
-        &self,
-        clusters: &[Cluster],
-        height: usize,
-        width: usize,
-    ) -> HashSet<usize> {
-        let mut neighbour_indices: HashSet<usize> = Default::default();
-        for (ny, nx) in self.neighbours(height, width) {
-            let neighbour_cluster_idx = clusters
-                .iter()
-                .position(|other| other.fields.contains(&(ny, nx)))
-                .unwrap();
-
-            neighbour_indices.insert(neighbour_cluster_idx);
+    #[test]
+    fn clusters_of_a_random_500x500_board() {
+        let instance = generate(&GeneratorOptions {
+            height: 500,
+            width: 500,
+            colors: 6,
+            seed: 7,
+            distribution: Distribution::Clustered { cohesion: 0.6 },
+        });
+        let clustering = Clustering::from_problem(&instance);
+
+        let tiles: usize = clustering.clusters.iter().map(|c| c.fields.len()).sum();
+        assert_eq!(tiles, 500 * 500);
+        for (id, cluster) in clustering.clusters.iter().enumerate() {
+            for &(y, x) in cluster.fields.iter() {
+                assert_eq!(instance.grid[y][x], cluster.color);
+                assert_eq!(clustering.cluster_id((y, x)), id);
+            }
+            for &neighbour in clustering.neighbours(id) {
+                assert_ne!(clustering.clusters[neighbour].color, cluster.color);
+                assert!(clustering.neighbours(neighbour).contains(&id));
+            }
         }
 
-        neighbour_indices
+        let region = instance.flooded_region();
+        let start = &clustering.clusters[clustering.cluster_id((0, 0))];
+        assert_eq!(region.len(), start.fields.len());
+        assert!(start.fields.iter().all(|p| region.contains(p)));
     }
 }
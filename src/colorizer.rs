@@ -51,6 +51,25 @@ impl Colorizer {
             _ => panic!("Invalid color index: {color_index}"),
         }
     }
+
+    /// RGB value of the color with the given index, matching the terminal colors used by [write]
+    ///
+    /// [write]: Colorizer::write
+    pub(crate) fn rgb(&self, color_index: usize) -> (u8, u8, u8) {
+        match self.color_mapping[color_index].as_str() {
+            "red" => (255, 0, 0),
+            "blue" => (0, 0, 255),
+            "green" => (0, 215, 0),
+            "yellow" => (255, 255, 0),
+            "orange" => (255, 135, 0),
+            "purple" => (135, 0, 175),
+            "light_green" => (0, 255, 175),
+            "pink" => (255, 95, 255),
+            "indigo" => (95, 95, 215),
+            "olive" => (135, 135, 0),
+            _ => panic!("Invalid color index: {color_index}"),
+        }
+    }
 }
 
 fn write_colored<T: owo_colors::Color>(
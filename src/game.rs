@@ -0,0 +1,285 @@
+//! Playing a game move by move on the cluster graph
+//!
+//! [Problem::apply_color] repaints the board and finds the flooded region tile by tile on every
+//! move. A [GameState] instead keeps track of the flooded clusters and the clusters adjacent to
+//! them, so a move only looks at the clusters it floods.
+//!
+//! [Problem::apply_color]: crate::problem::Problem::apply_color
+
+use crate::{
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+};
+
+/// What changed by a single move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveDelta {
+    pub color: Color,
+    /// Nodes flooded by the move, in ascending order
+    ///
+    /// Their tiles are given by [ClusterGraph::tiles].
+    pub clusters: Vec<usize>,
+    /// Number of tiles flooded by the move
+    pub tiles: usize,
+}
+
+/// A game in progress on the cluster graph of a board
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{game::GameState, graph::ClusterGraph, problem::Problem};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let graph = ClusterGraph::from_problem(&instance);
+/// let mut game = GameState::new(&graph);
+/// assert_eq!(game.candidates(), vec![1]);
+///
+/// let delta = game.play(1);
+/// assert_eq!((delta.clusters.clone(), delta.tiles), (vec![1, 3], 2));
+/// let tiles: Vec<_> = delta.clusters.iter().flat_map(|node| graph.tiles(*node)).collect();
+/// assert_eq!(tiles, vec![&(0, 1), &(1, 0)]);
+/// assert_eq!(game.flooded_tiles(), 3);
+/// assert_eq!(game.board().to_ascii(), "110\n100\n012\n");
+/// assert_eq!(game.frontier(0), &[2, 4]);
+/// assert_eq!(game.remaining_colors(), 3);
+///
+/// for color in [0, 1, 2] {
+///     game.play(color);
+/// }
+/// assert!(game.is_solved());
+/// assert_eq!(game.moves(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GameState<'g> {
+    graph: &'g ClusterGraph,
+    /// Color of the flooded region
+    color: Color,
+    flooded: Vec<bool>,
+    /// If a node is adjacent to the flooded region, but not flooded itself
+    in_frontier: Vec<bool>,
+    /// Nodes adjacent to the flooded region by color
+    frontier: Vec<Vec<usize>>,
+    /// Number of nodes not flooded yet by color
+    remaining: Vec<usize>,
+    flooded_clusters: usize,
+    flooded_tiles: usize,
+    moves: usize,
+}
+
+impl<'g> GameState<'g> {
+    /// Starts a game with only the start node flooded
+    pub fn new(graph: &'g ClusterGraph) -> Self {
+        let num_colors = graph.num_colors();
+
+        let mut remaining = vec![0; num_colors];
+        for node in 0..graph.len() {
+            remaining[graph.color(node) as usize] += 1;
+        }
+
+        let mut state = Self {
+            graph,
+            color: graph.color(graph.start()),
+            flooded: vec![false; graph.len()],
+            in_frontier: vec![false; graph.len()],
+            frontier: vec![vec![]; num_colors],
+            remaining,
+            flooded_clusters: 0,
+            flooded_tiles: 0,
+            moves: 0,
+        };
+        state.flood(graph.start());
+        state
+    }
+
+    pub fn graph(&self) -> &'g ClusterGraph {
+        self.graph
+    }
+
+    /// Color of the flooded region
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Number of moves played
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn is_flooded(&self, node: usize) -> bool {
+        self.flooded[node]
+    }
+
+    /// Number of flooded nodes
+    pub fn flooded_clusters(&self) -> usize {
+        self.flooded_clusters
+    }
+
+    /// Number of tiles in the flooded region
+    pub fn flooded_tiles(&self) -> usize {
+        self.flooded_tiles
+    }
+
+    /// Nodes of a color adjacent to the flooded region, which the color would flood next
+    pub fn frontier(&self, color: Color) -> &[usize] {
+        self.frontier
+            .get(color as usize)
+            .map_or(&[], |nodes| nodes.as_slice())
+    }
+
+    /// Number of nodes of a color that are not flooded yet
+    pub fn remaining(&self, color: Color) -> usize {
+        self.remaining.get(color as usize).copied().unwrap_or(0)
+    }
+
+    /// Number of colors with nodes that are not flooded yet, a lower bound of the moves needed
+    pub fn remaining_colors(&self) -> usize {
+        self.remaining.iter().filter(|nodes| **nodes > 0).count()
+    }
+
+    /// Colors that flood at least one more node, in ascending order
+    pub fn candidates(&self) -> Vec<Color> {
+        (0..self.frontier.len())
+            .filter(|color| !self.frontier[*color].is_empty())
+            .map(|color| color as Color)
+            .collect()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.flooded_clusters == self.graph.len()
+    }
+
+    /// Number of tiles a color would flood, without playing it
+    pub fn gain(&self, color: Color) -> usize {
+        self.frontier(color)
+            .iter()
+            .map(|node| self.graph.size(*node))
+            .sum()
+    }
+
+    /// The board in this state, with the flooded region painted in its color
+    pub fn board(&self) -> Problem {
+        let (height, width) = self.graph.dimensions();
+        let mut grid = vec![vec![0; width]; height];
+        for node in 0..self.graph.len() {
+            let color = if self.flooded[node] {
+                self.color
+            } else {
+                self.graph.color(node)
+            };
+            for &(y, x) in self.graph.tiles(node) {
+                grid[y][x] = color;
+            }
+        }
+        Problem { grid }
+    }
+
+    /// Floods all frontier nodes of a color and returns them
+    ///
+    /// Like on the board, choosing a color that is not adjacent to the flooded region still
+    /// counts as a move, but floods nothing.
+    pub fn play(&mut self, color: Color) -> MoveDelta {
+        self.moves += 1;
+        self.color = color;
+
+        let mut clusters = match self.frontier.get_mut(color as usize) {
+            Some(frontier) => std::mem::take(frontier),
+            None => vec![],
+        };
+        clusters.sort_unstable();
+
+        let before = self.flooded_tiles;
+        for node in clusters.iter() {
+            self.flood(*node);
+        }
+
+        MoveDelta {
+            color,
+            clusters,
+            tiles: self.flooded_tiles - before,
+        }
+    }
+
+    /// Marks a node as flooded and adds its neighbours to the frontier
+    fn flood(&mut self, node: usize) {
+        self.flooded[node] = true;
+        self.in_frontier[node] = false;
+        self.flooded_clusters += 1;
+        self.flooded_tiles += self.graph.size(node);
+        self.remaining[self.graph.color(node) as usize] -= 1;
+
+        for &neighbour in self.graph.neighbours(node) {
+            if !self.flooded[neighbour] && !self.in_frontier[neighbour] {
+                self.in_frontier[neighbour] = true;
+                self.frontier[self.graph.color(neighbour) as usize].push(neighbour);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generator::{generate, Distribution, GeneratorOptions},
+        util::Rng,
+    };
+
+    #[test]
+    fn matches_repainting_the_board() {
+        for seed in 0..10 {
+            let instance = generate(&GeneratorOptions {
+                height: 15,
+                width: 12,
+                colors: 5,
+                seed,
+                distribution: Distribution::Clustered { cohesion: 0.4 },
+            });
+            let graph = ClusterGraph::from_problem(&instance);
+            let mut game = GameState::new(&graph);
+            let mut board = instance.clone();
+            let mut rng = Rng::new(seed);
+
+            while !board.is_solved() {
+                // includes colors that flood nothing
+                let color = rng.below(5) as Color;
+                let before = board.flooded_region();
+                board.apply_color(color);
+                let after = board.flooded_region();
+
+                let delta = game.play(color);
+                assert_eq!(delta.tiles, after.len() - before.len());
+                let tiles: Vec<_> = delta
+                    .clusters
+                    .iter()
+                    .flat_map(|node| graph.tiles(*node))
+                    .collect();
+                assert!(tiles.iter().all(|tile| after.contains(tile)));
+                assert!(tiles.iter().all(|tile| !before.contains(tile)));
+
+                assert_eq!(game.board().to_ascii(), board.to_ascii());
+                assert_eq!(game.flooded_tiles(), after.len());
+                assert_eq!(game.color(), board.grid[0][0]);
+                assert_eq!(game.is_solved(), board.is_solved());
+            }
+            assert!(game.is_solved());
+        }
+    }
+
+    #[test]
+    fn gain_predicts_the_flooded_tiles() {
+        let instance: Problem = "0120\n2201\n1021\n".parse().unwrap();
+        let graph = ClusterGraph::from_problem(&instance);
+        let game = GameState::new(&graph);
+
+        for color in 0..3 {
+            let mut next = game.clone();
+            let delta = next.play(color);
+            assert_eq!(delta.tiles, game.gain(color));
+            assert_eq!(
+                next.flooded_tiles(),
+                instance.after(&[color]).flooded_region().len()
+            );
+        }
+        assert_eq!(game.candidates(), vec![1, 2]);
+    }
+}
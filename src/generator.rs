@@ -0,0 +1,285 @@
+//! Seeded generation of random problem instances
+
+use crate::{
+    problem::{Color, Problem},
+    util::Rng,
+};
+
+/// How colors are distributed over a generated grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// Every tile gets a color chosen uniformly at random
+    Uniform,
+    /// Every tile copies the color of its upper or left neighbour with probability `cohesion`,
+    /// which results in larger clusters
+    Clustered { cohesion: f64 },
+}
+
+/// Parameters of a generated problem instance
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    pub height: usize,
+    pub width: usize,
+    pub colors: usize,
+    pub seed: u64,
+    pub distribution: Distribution,
+}
+
+/// Generates a random problem instance
+///
+/// The same options always result in the same instance. Every color in `[0, colors)` is used at
+/// least once.
+///
+/// # Panics
+/// If `colors` is not in `[1, 10]` or the grid has less tiles than `colors`
+///
+/// # Example
+/// ```
+/// use color_flood_rs::generator::{generate, Distribution, GeneratorOptions};
+///
+/// let options = GeneratorOptions {
+///     height: 6,
+///     width: 8,
+///     colors: 4,
+///     seed: 42,
+///     distribution: Distribution::Uniform,
+/// };
+/// let instance = generate(&options);
+/// assert_eq!((instance.height(), instance.width()), (6, 8));
+/// assert_eq!(instance.num_colors(), 4);
+/// assert_eq!(instance.to_ascii(), generate(&options).to_ascii());
+/// ```
+pub fn generate(options: &GeneratorOptions) -> Problem {
+    let GeneratorOptions {
+        height,
+        width,
+        colors,
+        seed,
+        distribution,
+    } = *options;
+
+    assert!((1..=10).contains(&colors), "Colors must be in [1, 10]");
+    assert!(
+        height * width >= colors,
+        "Grid must have at least one tile per color"
+    );
+
+    let mut rng = Rng::new(seed);
+    let mut grid: Vec<Vec<Color>> = vec![vec![0; width]; height];
+
+    for y in 0..height {
+        for x in 0..width {
+            let copy = match distribution {
+                Distribution::Uniform => None,
+                Distribution::Clustered { cohesion } if rng.unit() < cohesion => {
+                    match (y.checked_sub(1), x.checked_sub(1)) {
+                        (Some(up), Some(_)) if rng.below(2) == 0 => Some(grid[up][x]),
+                        (Some(_), Some(left)) => Some(grid[y][left]),
+                        (Some(up), None) => Some(grid[up][x]),
+                        (None, Some(left)) => Some(grid[y][left]),
+                        (None, None) => None,
+                    }
+                }
+                Distribution::Clustered { .. } => None,
+            };
+
+            grid[y][x] = copy.unwrap_or_else(|| rng.below(colors) as Color);
+        }
+    }
+
+    // make colors gapless by placing every unused color on a tile whose color occurs elsewhere
+    for color in 0..colors as Color {
+        if grid.iter().flatten().any(|c| *c == color) {
+            continue;
+        }
+
+        loop {
+            let (y, x) = (rng.below(height), rng.below(width));
+            let current = grid[y][x];
+            if grid.iter().flatten().filter(|c| **c == current).count() > 1 {
+                grid[y][x] = color;
+                break;
+            }
+        }
+    }
+
+    Problem { grid }
+}
+
+/// Evaluation of a candidate board during [design]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Evaluation {
+    /// Proven optimal solution length
+    pub optimum: usize,
+    /// Number of distinct optimal solutions, if counted
+    pub optimal_solutions: Option<usize>,
+}
+
+/// Requirements of a board searched for by [design]
+#[derive(Debug, Clone)]
+pub struct DesignOptions {
+    /// Parameters of the initial board
+    pub board: GeneratorOptions,
+    /// Requested optimal solution length
+    pub moves: usize,
+    /// Require exactly one optimal color sequence
+    pub unique: bool,
+    /// Max number of mutations to try
+    pub iterations: usize,
+}
+
+/// Searches for a board whose optimal solution has exactly `options.moves` moves
+///
+/// Starting from a [generated](generate) board, random tiles are recolored and the mutated board
+/// is kept if its evaluation is not further away from the requirements than the current one.
+/// `evaluate` is expected to return the proven optimum of a board and, if uniqueness is requested,
+/// the number of optimal solutions. Boards it returns `None` for (e.g. on timeouts) are discarded.
+///
+/// Returns the first board meeting the requirements along with its evaluation, or `None` if none
+/// was found within `options.iterations` mutations.
+pub fn design<F>(options: &DesignOptions, mut evaluate: F) -> Option<(Problem, Evaluation)>
+where
+    F: FnMut(&Problem) -> Option<Evaluation>,
+{
+    // distance of an evaluation to the requirements, 0 if all are met
+    let distance = |evaluation: &Evaluation| {
+        let moves = evaluation.optimum.abs_diff(options.moves);
+        let solutions = match (options.unique, evaluation.optimal_solutions) {
+            (true, Some(count)) => count.saturating_sub(1),
+            (true, None) => 1,
+            (false, _) => 0,
+        };
+        (moves, solutions)
+    };
+
+    let meets_requirements = |evaluation: &Evaluation| distance(evaluation) == (0, 0);
+
+    let mut rng = Rng::new(options.board.seed);
+    let mut current = generate(&options.board);
+    let mut current_evaluation = evaluate(&current);
+
+    for _ in 0..options.iterations {
+        if let Some(evaluation) = current_evaluation.filter(meets_requirements) {
+            return Some((current, evaluation));
+        }
+
+        let candidate = mutate(&current, options.board.colors, &mut rng);
+        let Some(evaluation) = evaluate(&candidate) else {
+            continue;
+        };
+
+        let keep =
+            current_evaluation.is_none_or(|current| distance(&evaluation) <= distance(&current));
+        if keep {
+            current = candidate;
+            current_evaluation = Some(evaluation);
+        }
+    }
+
+    current_evaluation
+        .filter(meets_requirements)
+        .map(|evaluation| (current, evaluation))
+}
+
+/// Recolors up to three random tiles, keeping all `colors` in use
+fn mutate(instance: &Problem, colors: usize, rng: &mut Rng) -> Problem {
+    let mut grid = instance.grid.clone();
+    let (height, width) = (instance.height(), instance.width());
+
+    for _ in 0..=rng.below(3) {
+        let (y, x) = (rng.below(height), rng.below(width));
+        let current = grid[y][x];
+        let color = rng.below(colors) as Color;
+        let occurrences = grid.iter().flatten().filter(|c| **c == current).count();
+        if color != current && occurrences > 1 {
+            grid[y][x] = color;
+        }
+    }
+
+    Problem { grid }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(seed: u64, distribution: Distribution) -> GeneratorOptions {
+        GeneratorOptions {
+            height: 12,
+            width: 9,
+            colors: 5,
+            seed,
+            distribution,
+        }
+    }
+
+    #[test]
+    fn generates_the_same_board_for_the_same_seed() {
+        for distribution in [
+            Distribution::Uniform,
+            Distribution::Clustered { cohesion: 0.7 },
+        ] {
+            let board = generate(&options(3, distribution)).to_ascii();
+            assert_eq!(generate(&options(3, distribution)).to_ascii(), board);
+            assert_ne!(generate(&options(4, distribution)).to_ascii(), board);
+        }
+    }
+
+    #[test]
+    fn uses_every_color() {
+        for seed in 0..20 {
+            // cohesion 1 copies the top left tile everywhere, so colors must be placed afterwards
+            let instance = generate(&GeneratorOptions {
+                height: 2,
+                width: 3,
+                colors: 6,
+                seed,
+                distribution: Distribution::Clustered { cohesion: 1.0 },
+            });
+            let mut colors: Vec<Color> = instance.grid.iter().flatten().copied().collect();
+            colors.sort_unstable();
+            assert_eq!(colors, vec![0, 1, 2, 3, 4, 5]);
+        }
+    }
+
+    #[test]
+    fn clustered_boards_have_fewer_clusters() {
+        let clusters = |distribution| {
+            crate::cluster::Cluster::from_problem(&generate(&GeneratorOptions {
+                height: 30,
+                width: 30,
+                ..options(1, distribution)
+            }))
+            .len()
+        };
+        assert!(
+            clusters(Distribution::Clustered { cohesion: 0.8 }) < clusters(Distribution::Uniform)
+        );
+    }
+
+    #[test]
+    fn designs_deterministically() {
+        let design_options = DesignOptions {
+            board: options(9, Distribution::Uniform),
+            moves: 30,
+            unique: false,
+            iterations: 5000,
+        };
+        let zeros = |board: &Problem| board.grid.iter().flatten().filter(|c| **c == 0).count();
+        // stands in for a solver, evaluating boards by their number of tiles of color 0
+        let evaluate = |board: &Problem| {
+            Some(Evaluation {
+                optimum: zeros(board),
+                optimal_solutions: None,
+            })
+        };
+
+        let (board, evaluation) = design(&design_options, evaluate).unwrap();
+        assert_eq!(evaluation.optimum, 30);
+        assert_eq!(zeros(&board), 30);
+        let (again, _) = design(&design_options, evaluate).unwrap();
+        assert_eq!(again.to_ascii(), board.to_ascii());
+
+        assert!(design(&design_options, |_| None).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! The cluster adjacency graph of a problem instance
+//!
+//! Every cluster of a board is a node, and two nodes are connected if their clusters touch. A
+//! move floods all clusters of the chosen color adjacent to the flooded ones, so the game can be
+//! played on this graph without looking at single tiles.
+
+use std::{collections::VecDeque, fmt::Write};
+
+use crate::{
+    cluster::Clustering,
+    colorizer::Colorizer,
+    problem::{Color, Problem},
+    util::Point,
+};
+
+/// The cluster adjacency graph of a problem instance
+///
+/// Nodes are identified by the index of their cluster in [Cluster::from_problem].
+///
+/// [Cluster::from_problem]: crate::cluster::Cluster::from_problem
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{graph::ClusterGraph, problem::Problem};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let graph = ClusterGraph::from_problem(&instance);
+/// assert_eq!(graph.len(), 7);
+/// assert_eq!(graph.start(), 0);
+/// assert_eq!((graph.color(2), graph.size(2), graph.degree(2)), (0, 3, 4));
+/// assert_eq!(graph.tiles(2), &[(0, 2), (1, 1), (1, 2)]);
+/// assert_eq!(graph.distances(0), vec![0, 1, 2, 1, 2, 3, 3]);
+/// assert_eq!(graph.eccentricity(0), 3);
+/// assert_eq!(graph.diameter(), 3);
+/// assert_eq!(graph.edges().count(), 9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClusterGraph {
+    colors: Vec<Color>,
+    tiles: Vec<Vec<Point>>,
+    neighbours: Vec<Vec<usize>>,
+    start: usize,
+    height: usize,
+    width: usize,
+}
+
+impl ClusterGraph {
+    pub fn from_problem(instance: &Problem) -> Self {
+        Self::from_clustering(&Clustering::from_problem(instance))
+    }
+
+    pub fn from_clustering(clustering: &Clustering) -> Self {
+        Self {
+            colors: clustering
+                .clusters
+                .iter()
+                .map(|cluster| cluster.color)
+                .collect(),
+            tiles: clustering
+                .clusters
+                .iter()
+                .map(|cluster| cluster.fields.clone())
+                .collect(),
+            neighbours: (0..clustering.len())
+                .map(|node| clustering.neighbours(node).to_vec())
+                .collect(),
+            start: clustering.cluster_id((0, 0)),
+            height: clustering.dimensions().0,
+            width: clustering.dimensions().1,
+        }
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// The node containing the top left tile, which is flooded from the start
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn color(&self, node: usize) -> Color {
+        self.colors[node]
+    }
+
+    /// Max color number used by any node
+    pub fn num_colors(&self) -> usize {
+        self.colors
+            .iter()
+            .map(|color| *color as usize + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of tiles of a node's cluster
+    pub fn size(&self, node: usize) -> usize {
+        self.tiles[node].len()
+    }
+
+    /// Tiles of a node's cluster, sorted row by row
+    pub fn tiles(&self, node: usize) -> &[Point] {
+        &self.tiles[node]
+    }
+
+    /// Height and width of the board the graph was built from
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    /// Nodes adjacent to a node, in ascending order
+    pub fn neighbours(&self, node: usize) -> &[usize] {
+        &self.neighbours[node]
+    }
+
+    /// Number of nodes adjacent to a node
+    pub fn degree(&self, node: usize) -> usize {
+        self.neighbours[node].len()
+    }
+
+    /// All edges, each once as `(a, b)` with `a < b`
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours
+            .iter()
+            .enumerate()
+            .flat_map(|(a, neighbours)| {
+                neighbours
+                    .iter()
+                    .filter(move |b| a < **b)
+                    .map(move |b| (a, *b))
+            })
+    }
+
+    /// Length of the shortest path from `node` to every node
+    ///
+    /// Boards are connected, so every node is reachable.
+    pub fn distances(&self, node: usize) -> Vec<usize> {
+        let mut distances = vec![usize::MAX; self.len()];
+        distances[node] = 0;
+        let mut queue = VecDeque::from([node]);
+
+        while let Some(node) = queue.pop_front() {
+            for &neighbour in self.neighbours[node].iter() {
+                if distances[neighbour] == usize::MAX {
+                    distances[neighbour] = distances[node] + 1;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Max distance from `node` to any node
+    ///
+    /// The eccentricity of the start node is a lower bound of the solution length, as every move
+    /// advances the flooded region by at most one node.
+    pub fn eccentricity(&self, node: usize) -> usize {
+        self.distances(node).into_iter().max().unwrap_or(0)
+    }
+
+    /// Max distance between any two nodes
+    ///
+    /// Runs a breadth first search from every node, which takes O(V·(V+E)) time. Prefer
+    /// [ClusterGraph::eccentricity] of the start node where it suffices.
+    pub fn diameter(&self) -> usize {
+        (0..self.len())
+            .map(|node| self.eccentricity(node))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The graph in the DOT language of [GraphViz](https://graphviz.org)
+    ///
+    /// Nodes are labeled with their id and size and filled with their color. The start node is
+    /// drawn with a double border.
+    pub fn to_dot(&self) -> String {
+        let colorizer = Colorizer::new();
+        let mut dot = String::from("graph clusters {\n    node [shape=circle, style=filled];\n");
+
+        for node in 0..self.len() {
+            let (r, g, b) = colorizer.rgb(self.colors[node] as usize);
+            let _ = write!(
+                dot,
+                "    {node} [label=\"{node}\\n{}\", fillcolor=\"#{r:02x}{g:02x}{b:02x}\"",
+                self.size(node),
+            );
+            if node == self.start {
+                dot.push_str(", peripheries=2");
+            }
+            dot.push_str("];\n");
+        }
+        for (a, b) in self.edges() {
+            let _ = writeln!(dot, "    {a} -- {b};");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
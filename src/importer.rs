@@ -0,0 +1,544 @@
+//! Importing problem instances from images of boards, e.g. screenshots
+
+use std::{fmt::Display, io::Read};
+
+use crate::{
+    problem::{Color, Problem},
+    raster::{self, Frame},
+};
+
+/// An RGB image
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGB values, one per pixel
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl From<&Frame> for Image {
+    fn from(frame: &Frame) -> Self {
+        let palette = raster::palette();
+        let pixels = frame
+            .pixels
+            .iter()
+            .map(|index| {
+                let offset = 3 * *index as usize;
+                [palette[offset], palette[offset + 1], palette[offset + 2]]
+            })
+            .collect();
+
+        Self {
+            width: frame.width as usize,
+            height: frame.height as usize,
+            pixels,
+        }
+    }
+}
+
+/// Reasons why a board could not be imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The input could not be read
+    Io(String),
+    /// The input is neither a PNG nor a PPM image
+    UnsupportedFormat,
+    /// The image is corrupt
+    InvalidImage(String),
+    /// The grid dimensions are zero or larger than the image
+    InvalidGrid { rows: usize, columns: usize },
+    /// No grid could be detected in the image
+    NoGridDetected,
+    /// The sampled colors form more than 10 clusters
+    TooManyColors { found: usize },
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "could not read image: {err}"),
+            ImportError::UnsupportedFormat => write!(f, "image must be a PNG or PPM file"),
+            ImportError::InvalidImage(err) => write!(f, "invalid image: {err}"),
+            ImportError::InvalidGrid { rows, columns } => {
+                write!(f, "a {rows}x{columns} grid does not fit the image")
+            }
+            ImportError::NoGridDetected => write!(
+                f,
+                "could not detect the grid, please give its dimensions explicitly"
+            ),
+            ImportError::TooManyColors { found } => write!(
+                f,
+                "found {found} distinct colors, but at most 10 are supported \
+                 (try a larger tolerance or give the number of colors)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl Image {
+    /// Reads a PNG or PPM (`P3` or `P6`) image, detected by its first bytes
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, ImportError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| ImportError::Io(err.to_string()))?;
+
+        if bytes.starts_with(b"\x89PNG") {
+            Self::from_png(&bytes)
+        } else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+            Self::from_ppm(&bytes)
+        } else {
+            Err(ImportError::UnsupportedFormat)
+        }
+    }
+
+    fn from_png(bytes: &[u8]) -> Result<Self, ImportError> {
+        let invalid = |err: png::DecodingError| ImportError::InvalidImage(err.to_string());
+
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(invalid)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(invalid)?;
+        let buf = &buf[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgb => buf.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect(),
+            png::ColorType::Rgba => buf.chunks_exact(4).map(|p| [p[0], p[1], p[2]]).collect(),
+            png::ColorType::Grayscale => buf.iter().map(|v| [*v; 3]).collect(),
+            png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).map(|p| [p[0]; 3]).collect(),
+            png::ColorType::Indexed => unreachable!("Indexed images are expanded to RGB"),
+        };
+
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    fn from_ppm(bytes: &[u8]) -> Result<Self, ImportError> {
+        let invalid = |msg: &str| ImportError::InvalidImage(msg.to_owned());
+
+        // header fields are separated by whitespace and may be followed by comments
+        let mut pos = 2;
+        let mut header = [0usize; 3];
+        for field in header.iter_mut() {
+            loop {
+                match bytes.get(pos) {
+                    Some(b'#') => {
+                        while bytes.get(pos).is_some_and(|b| *b != b'\n') {
+                            pos += 1;
+                        }
+                    }
+                    Some(b) if b.is_ascii_whitespace() => pos += 1,
+                    _ => break,
+                }
+            }
+            let start = pos;
+            while bytes.get(pos).is_some_and(u8::is_ascii_digit) {
+                pos += 1;
+            }
+            *field = std::str::from_utf8(&bytes[start..pos])
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| invalid("malformed PPM header"))?;
+        }
+
+        let [width, height, max_value] = header;
+        if max_value == 0 || max_value > u16::MAX as usize {
+            return Err(invalid("PPM max value must be in [1, 65535]"));
+        }
+
+        // every sample takes at least one byte, so larger headers can be rejected before reading
+        let data = bytes.get(pos + 1..).unwrap_or_default();
+        let num_samples = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .filter(|num_samples| *num_samples <= data.len())
+            .ok_or_else(|| invalid("PPM data is truncated"))?;
+
+        let samples: Vec<usize> = if bytes.starts_with(b"P6") {
+            // a single whitespace character separates the header from the binary data
+            if max_value < 256 {
+                data.iter().take(num_samples).map(|v| *v as usize).collect()
+            } else {
+                data.chunks_exact(2)
+                    .take(num_samples)
+                    .map(|v| u16::from_be_bytes([v[0], v[1]]) as usize)
+                    .collect()
+            }
+        } else {
+            std::str::from_utf8(&bytes[pos..])
+                .map_err(|_| invalid("PPM data must be ASCII"))?
+                .split_ascii_whitespace()
+                .take(num_samples)
+                .map(|v| v.parse().map_err(|_| invalid("malformed PPM data")))
+                .collect::<Result<_, _>>()?
+        };
+
+        if samples.len() < num_samples {
+            return Err(invalid("PPM data is truncated"));
+        }
+
+        let scale = |v: usize| (v.min(max_value) * 255 / max_value) as u8;
+        let pixels = samples
+            .chunks_exact(3)
+            .map(|p| [scale(p[0]), scale(p[1]), scale(p[2])])
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn pixel(&self, y: usize, x: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Parameters of an import
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Number of rows and columns of the board, detected if missing
+    pub grid: Option<(usize, usize)>,
+    /// Number of colors on the board, derived from `tolerance` if missing
+    pub colors: Option<usize>,
+    /// Max distance (in RGB space) of a sampled color to the color it is assigned to, if the
+    /// number of colors is not given
+    pub tolerance: f64,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            grid: None,
+            colors: None,
+            tolerance: 48.0,
+        }
+    }
+}
+
+/// Imports a board from an image, which must be cropped to the board
+///
+/// Every tile's color is sampled as the mean of its central area, so that tile outlines don't
+/// distort it. The samples are then grouped into a palette, either by k-means if the number of
+/// colors is given, or by assigning each sample to the first palette color within
+/// `options.tolerance`. Colors are numbered by their first occurrence, so the top left tile
+/// always gets color 0.
+///
+/// If the grid dimensions are not given, they are detected from the positions of color edges,
+/// which works best if the board is not skewed and its tiles are outlined or mostly differently
+/// colored.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{
+///     importer::{import, Image, ImportOptions},
+///     problem::Problem,
+///     raster::{render_frame, RasterOptions},
+/// };
+///
+/// let instance: Problem = "0120\n2201\n1021".parse().unwrap();
+/// let image = Image::from(&render_frame(&instance, &RasterOptions::default()));
+///
+/// let imported = import(&image, &ImportOptions::default()).unwrap();
+/// assert_eq!(imported.to_ascii(), instance.to_ascii());
+/// ```
+pub fn import(image: &Image, options: &ImportOptions) -> Result<Problem, ImportError> {
+    let (rows, columns) = match options.grid {
+        Some(grid) => grid,
+        None => detect_grid(image).ok_or(ImportError::NoGridDetected)?,
+    };
+    if rows == 0 || columns == 0 || rows > image.height || columns > image.width {
+        return Err(ImportError::InvalidGrid { rows, columns });
+    }
+
+    let samples: Vec<[f64; 3]> = (0..rows)
+        .flat_map(|y| (0..columns).map(move |x| (y, x)))
+        .map(|(y, x)| sample(image, (y, x), (rows, columns)))
+        .collect();
+
+    let assignment = match options.colors {
+        Some(colors) => k_means(&samples, colors),
+        None => cluster_by_tolerance(&samples, options.tolerance),
+    };
+
+    // number colors by first occurrence, which also removes gaps of empty clusters
+    let mut numbering: Vec<Option<Color>> = vec![];
+    let mut next: usize = 0;
+    let mut grid = vec![vec![0; columns]; rows];
+    for (idx, cluster) in assignment.into_iter().enumerate() {
+        if numbering.len() <= cluster {
+            numbering.resize(cluster + 1, None);
+        }
+        let color = *numbering[cluster].get_or_insert_with(|| {
+            next += 1;
+            (next - 1) as Color
+        });
+        grid[idx / columns][idx % columns] = color;
+    }
+
+    if next > 10 {
+        return Err(ImportError::TooManyColors { found: next });
+    }
+
+    Ok(Problem { grid })
+}
+
+/// Mean color of the central half of tile `(y, x)`
+fn sample(image: &Image, (y, x): (usize, usize), (rows, columns): (usize, usize)) -> [f64; 3] {
+    let (top, bottom) = (y * image.height / rows, (y + 1) * image.height / rows);
+    let (left, right) = (x * image.width / columns, (x + 1) * image.width / columns);
+    let (dy, dx) = ((bottom - top) / 4, (right - left) / 4);
+
+    let mut sum = [0.0; 3];
+    let mut count = 0.0;
+    for py in top + dy..bottom - dy {
+        for px in left + dx..right - dx {
+            let pixel = image.pixel(py, px);
+            for (s, v) in sum.iter_mut().zip(pixel) {
+                *s += v as f64;
+            }
+            count += 1.0;
+        }
+    }
+
+    sum.map(|s| s / count)
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Assigns every sample to the first cluster whose mean is within `tolerance`
+fn cluster_by_tolerance(samples: &[[f64; 3]], tolerance: f64) -> Vec<usize> {
+    let mut means: Vec<([f64; 3], f64)> = vec![];
+
+    samples
+        .iter()
+        .map(|sample| {
+            let found = means
+                .iter()
+                .position(|(mean, _)| distance(mean, sample) <= tolerance);
+            match found {
+                Some(idx) => {
+                    let (mean, count) = &mut means[idx];
+                    *count += 1.0;
+                    for (m, s) in mean.iter_mut().zip(sample) {
+                        *m += (s - *m) / *count;
+                    }
+                    idx
+                }
+                None => {
+                    means.push((*sample, 1.0));
+                    means.len() - 1
+                }
+            }
+        })
+        .collect()
+}
+
+/// Assigns every sample to one of `k` clusters by Lloyd's algorithm, seeded with the samples
+/// furthest apart from each other
+fn k_means(samples: &[[f64; 3]], k: usize) -> Vec<usize> {
+    let nearest = |means: &[[f64; 3]], sample: &[f64; 3]| {
+        (0..means.len())
+            .min_by(|a, b| distance(&means[*a], sample).total_cmp(&distance(&means[*b], sample)))
+            .unwrap()
+    };
+
+    let mut means = vec![samples[0]];
+    while means.len() < k.clamp(1, samples.len()) {
+        let furthest = samples
+            .iter()
+            .max_by(|a, b| {
+                let da = distance(&means[nearest(&means, a)], a);
+                let db = distance(&means[nearest(&means, b)], b);
+                da.total_cmp(&db)
+            })
+            .unwrap();
+        means.push(*furthest);
+    }
+
+    let mut assignment: Vec<usize> = samples.iter().map(|s| nearest(&means, s)).collect();
+    for _ in 0..100 {
+        for (idx, mean) in means.iter_mut().enumerate() {
+            let members: Vec<_> = samples
+                .iter()
+                .zip(assignment.iter())
+                .filter(|(_, cluster)| **cluster == idx)
+                .map(|(sample, _)| sample)
+                .collect();
+            if !members.is_empty() {
+                *mean = [0, 1, 2]
+                    .map(|c| members.iter().map(|m| m[c]).sum::<f64>() / members.len() as f64);
+            }
+        }
+
+        let next: Vec<usize> = samples.iter().map(|s| nearest(&means, s)).collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+    }
+
+    assignment
+}
+
+/// Detects the number of rows and columns from the positions of color edges
+fn detect_grid(image: &Image) -> Option<(usize, usize)> {
+    // number of pixel pairs across each vertical (resp. horizontal) line that differ in color
+    let mut column_edges = vec![0usize; image.width];
+    let mut row_edges = vec![0usize; image.height];
+    let differs = |a: [u8; 3], b: [u8; 3]| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.abs_diff(b) as u32)
+            .sum::<u32>()
+            > 48
+    };
+
+    for y in 0..image.height {
+        for (x, count) in column_edges.iter_mut().enumerate().skip(1) {
+            if differs(image.pixel(y, x - 1), image.pixel(y, x)) {
+                *count += 1;
+            }
+        }
+    }
+    for (y, count) in row_edges.iter_mut().enumerate().skip(1) {
+        for x in 0..image.width {
+            if differs(image.pixel(y - 1, x), image.pixel(y, x)) {
+                *count += 1;
+            }
+        }
+    }
+
+    Some((detect_period(&row_edges)?, detect_period(&column_edges)?))
+}
+
+/// Finds the number of equally sized cells whose boundaries best explain the edge counts
+///
+/// A candidate's score is the share of all edges lying on its cell boundaries. Multiples of the
+/// real number of cells score equally well, so the smallest number of cells scoring close to the
+/// best is chosen.
+fn detect_period(edges: &[usize]) -> Option<usize> {
+    let len = edges.len();
+    let total = edges.iter().sum::<usize>() as f64;
+    if total == 0.0 {
+        return None;
+    }
+
+    // edges may be off by a pixel due to rounding and outlines
+    let edges_around = |pos: usize| edges[pos - 1..(pos + 2).min(len)].iter().sum::<usize>();
+
+    let scores: Vec<(usize, f64)> = (2..=len / 4)
+        .map(|cells| {
+            let covered: usize = (1..cells).map(|idx| edges_around(idx * len / cells)).sum();
+            (cells, covered as f64 / total)
+        })
+        .collect();
+
+    let best = scores.iter().map(|(_, score)| *score).fold(0.0, f64::max);
+    if best < 0.5 {
+        return None;
+    }
+
+    scores
+        .iter()
+        .find(|(_, score)| *score >= 0.9 * best)
+        .map(|(cells, _)| *cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::{render_frame, RasterOptions};
+
+    #[test]
+    fn reads_ascii_and_binary_ppm() {
+        let ascii = "P3\n# comment\n2 1\n255\n255 0 0  0 0 255\n".as_bytes();
+        let binary = &b"P6 2 1 255\n\xff\x00\x00\x00\x00\xff"[..];
+        for bytes in [ascii, binary] {
+            let image = Image::from_reader(bytes).unwrap();
+            assert_eq!((image.width, image.height), (2, 1));
+            assert_eq!(image.pixels, vec![[255, 0, 0], [0, 0, 255]]);
+        }
+    }
+
+    #[test]
+    fn detects_periods_of_edge_counts() {
+        // 5 cells of 8 pixels, with edges on the boundaries only
+        let mut edges = vec![0; 40];
+        for idx in 1..5 {
+            edges[idx * 8] = 10;
+        }
+        assert_eq!(detect_period(&edges), Some(5));
+
+        // boundaries off by a pixel and a little noise elsewhere
+        edges[16] = 0;
+        edges[17] = 10;
+        edges[3] = 1;
+        assert_eq!(detect_period(&edges), Some(5));
+
+        assert_eq!(detect_period(&[0; 40]), None);
+    }
+
+    #[test]
+    fn detects_grids_of_rendered_boards() {
+        let instance: Problem = "01210\n10102\n22011\n".parse().unwrap();
+        for outlines in [true, false] {
+            let frame = render_frame(
+                &instance,
+                &RasterOptions {
+                    tile_size: 10,
+                    outlines,
+                    highlight_flooded: false,
+                },
+            );
+            let image = Image::from(&frame);
+            assert_eq!(detect_grid(&image), Some((3, 5)));
+
+            let imported = import(&image, &ImportOptions::default()).unwrap();
+            assert_eq!(imported.to_ascii(), instance.to_ascii());
+        }
+    }
+
+    #[test]
+    fn rejects_grids_larger_than_the_image() {
+        let image = Image::from_reader("P3 2 1 255\n0 0 0 255 255 255".as_bytes()).unwrap();
+        let options = ImportOptions {
+            grid: Some((2, 2)),
+            ..Default::default()
+        };
+        assert_eq!(
+            import(&image, &options).err(),
+            Some(ImportError::InvalidGrid {
+                rows: 2,
+                columns: 2
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_ppm_headers_larger_than_the_data() {
+        let truncated = ImportError::InvalidImage("PPM data is truncated".to_owned());
+        let header = |header: &str| Image::from_reader(header.as_bytes()).err();
+
+        assert_eq!(header("P6 2 2 255\n\0\0\0"), Some(truncated.clone()));
+        assert_eq!(header("P3 2 2 255\n0 0 0"), Some(truncated.clone()));
+        // 3 * width * height overflows
+        let huge = format!("P6 {} {} 255\n\0\0\0", usize::MAX / 2, 2);
+        assert_eq!(header(&huge), Some(truncated.clone()));
+        let huge = format!("P3 {} {} 255\n0 0 0", usize::MAX, usize::MAX);
+        assert_eq!(header(&huge), Some(truncated));
+    }
+}
@@ -50,12 +50,44 @@
 //! - Neighbours(i) := indices of all clusters adjacent to cluster_i
 //! - Color(i) := color of cluster_i
 //!
+//! ## Solving without z3
+//! The SMT backend lives in [solver] and is only available with the `z3` feature. Without it,
+//! [search] finds minimal solutions by heuristic search in pure Rust.
+//!
+//! ## Cargo features
+//! - `z3`: the SMT backend in [solver], which needs a local installation of z3. Enabled by default
+//! - `play`: playing instances interactively in the terminal, in `play`
+//! - `server`: the HTTP JSON API, in `server`
+//! - `cli`: the command line interface, which needs `play` and `server`. Enabled by default.
+//!   Without `z3` it only solves by [search], and the subcommands that need z3 are left out
+//!
+//! Without default features the library is pure Rust and builds for `wasm32-unknown-unknown`.
+//!
 
+pub mod analysis;
+pub mod batch;
+pub mod bench;
+pub mod cancel;
+#[cfg(feature = "cli")]
 pub mod cli;
 pub mod cluster;
 mod colorizer;
+pub mod game;
+pub mod generator;
+pub mod graph;
+pub mod importer;
+#[cfg(feature = "play")]
+pub mod play;
 pub mod printer;
 pub mod problem;
+pub mod raster;
+pub mod rating;
+pub mod report;
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod solution;
+#[cfg(feature = "z3")]
 pub mod solver;
+pub mod svg;
 mod util;
@@ -0,0 +1,185 @@
+//! Playing problem instances interactively in the terminal
+
+use std::io::Write;
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::{
+    colorizer::Colorizer,
+    game::GameState,
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+};
+
+/// Best next move from some state, as suggested by a solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    pub color: Color,
+    /// Number of moves needed to solve the instance after this one
+    pub remaining: usize,
+}
+
+/// Length of the best known solution of the played instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub moves: usize,
+    /// If the solution is proven to be of minimal length
+    pub optimal: bool,
+}
+
+/// Disables the terminal's raw mode when dropped
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lets the user play an instance in the terminal until it is solved or the user quits
+///
+/// Colors are chosen by pressing their digit. `h` asks `hint` for the best next move from the
+/// current board, `u` undoes the last move, `r` restarts and `q` quits.
+///
+/// Returns the number of moves made if the instance was solved.
+pub fn play<H>(
+    instance: &Problem,
+    target: Option<Target>,
+    mut hint: H,
+) -> std::io::Result<Option<usize>>
+where
+    H: FnMut(&Problem) -> Option<Hint>,
+{
+    let _raw_mode = RawMode::enable()?;
+    let mut stdout = std::io::stdout();
+
+    let colors = instance.num_colors();
+    let graph = ClusterGraph::from_problem(instance);
+    let mut history: Vec<GameState> = vec![];
+    let mut game = GameState::new(&graph);
+    let mut message = String::new();
+
+    loop {
+        let board = game.board();
+        draw(&mut stdout, &board, colors, game.moves(), target, &message)?;
+        message.clear();
+
+        if game.is_solved() {
+            return Ok(Some(game.moves()));
+        }
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        match code {
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Char(ch @ '0'..='9') => {
+                let color = ch as Color - b'0';
+                if color as usize >= colors {
+                    message = format!("There is no color {color}");
+                } else if color == game.color() {
+                    message = format!("Color {color} is already flooded");
+                } else {
+                    history.push(game.clone());
+                    game.play(color);
+                }
+            }
+            KeyCode::Char('u') => match history.pop() {
+                Some(previous) => game = previous,
+                None => message = "Nothing to undo".to_owned(),
+            },
+            KeyCode::Char('r') => {
+                history.clear();
+                game = GameState::new(&graph);
+            }
+            KeyCode::Char('h') => {
+                draw(
+                    &mut stdout,
+                    &board,
+                    colors,
+                    game.moves(),
+                    target,
+                    "Thinking...",
+                )?;
+                message = match hint(&board) {
+                    Some(Hint { color, remaining }) => {
+                        let mut swatch = String::new();
+                        Colorizer::new()
+                            .write(&mut swatch, "  ", color as usize)
+                            .unwrap();
+                        format!("Hint: {color} {swatch}, then {remaining} more moves")
+                    }
+                    None => "No hint available".to_owned(),
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Redraws the whole screen, listing `colors` colors to choose from
+fn draw<W: Write>(
+    w: &mut W,
+    board: &Problem,
+    colors: usize,
+    moves: usize,
+    target: Option<Target>,
+    message: &str,
+) -> std::io::Result<()> {
+    execute!(w, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    // raw mode requires explicit carriage returns
+    write!(w, "{}\r\n", board.to_string().replace('\n', "\r\n"))?;
+
+    let colorizer = Colorizer::new();
+    let mut legend = String::new();
+    for color in 0..colors {
+        colorizer.write(&mut legend, "  ", color).unwrap();
+        legend.push_str(&format!(" {color}  "));
+    }
+    write!(w, "{legend}\r\n\r\n")?;
+
+    match target {
+        Some(Target {
+            moves: target,
+            optimal,
+        }) => write!(
+            w,
+            "Moves: {moves} / {target} ({})\r\n",
+            if optimal { "optimal" } else { "best known" }
+        )?,
+        None => write!(w, "Moves: {moves}\r\n")?,
+    }
+
+    if board.is_solved() {
+        write!(w, "Solved in {moves} moves!\r\n")?;
+    } else {
+        write!(w, "{message}\r\n\r\n")?;
+        write!(
+            w,
+            "0-9: flood color   h: hint   u: undo   r: restart   q: quit\r\n"
+        )?;
+    }
+
+    w.flush()
+}
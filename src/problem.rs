@@ -4,7 +4,7 @@ use crate::{
     colorizer::Colorizer,
     util::{neighbours, Point},
 };
-use std::{collections::HashSet, fmt::Display};
+use std::{collections::HashSet, fmt::Display, io::BufRead, str::FromStr};
 
 /// A number denoting a color (by index)
 pub type Color = u8;
@@ -31,36 +31,222 @@ impl Display for Problem {
     }
 }
 
+impl FromStr for Problem {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+/// Reasons why a problem instance could not be parsed
+///
+/// Line and column numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input could not be read
+    Io(String),
+    /// The input does not contain any rows
+    Empty,
+    /// A character other than a digit was found
+    InvalidChar {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    /// A row's length differs from the length of the first row
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A color below the max color is never used
+    MissingColor { color: Color, num_colors: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Io(err) => write!(f, "could not read input: {err}"),
+            ParseError::Empty => write!(f, "problem must not be empty"),
+            ParseError::InvalidChar { line, column, ch } => write!(
+                f,
+                "line {line}, column {column}: unexpected character {ch:?}, expected a digit 0-9"
+            ),
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: row has {found} tiles, expected {expected} like the first row"
+            ),
+            ParseError::MissingColor { color, num_colors } => write!(
+                f,
+                "color {color} is never used, but colors must be numbered 0-{} without gaps",
+                num_colors - 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 impl Problem {
     /// Construct a problem instance from stdin
     ///
+    /// See [Problem::from_reader] for the expected format.
+    pub fn from_stdin() -> Result<Self, ParseError> {
+        Self::from_reader(std::io::stdin().lock())
+    }
+
+    /// Construct a problem instance from a reader
+    ///
     /// Problems should be encoded as
-    /// ```
+    /// ```text
     /// 010
     /// 102
     /// 201
     /// ```
-    // where every digit denotes a color between 0 and 9 (inclusive).
-    pub fn from_stdin() -> Self {
-        let grid: Vec<Vec<Color>> = std::io::stdin()
-            .lines()
-            .map(|line| {
-                line.unwrap()
-                    .chars()
-                    .map(|ch| ch.to_digit(10).unwrap() as u8)
-                    .collect()
-            })
-            .collect();
+    /// where every digit denotes a color between 0 and 9 (inclusive). All rows must have the same
+    /// length and colors must be numbered without gaps, starting at 0. Leading and trailing blank
+    /// lines, including lines of whitespace only, are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::problem::{ParseError, Problem};
+    ///
+    /// let instance = Problem::from_reader("010\n102\n201\n".as_bytes()).unwrap();
+    /// assert_eq!(instance.num_colors(), 3);
+    ///
+    /// let instance = Problem::from_reader("010\r\n102\r\n201\r\n".as_bytes()).unwrap();
+    /// assert_eq!(instance.width(), 3);
+    ///
+    /// let err = Problem::from_reader("010\n1 2\n".as_bytes()).err();
+    /// assert_eq!(err, Some(ParseError::InvalidChar { line: 2, column: 2, ch: ' ' }));
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ParseError> {
+        let mut grid: Vec<Vec<Color>> = vec![];
+        // line number (1-based) of the first row
+        let mut first_line = 0;
+        // blank lines after the last row, which are only rows if another row follows
+        let mut blank: Vec<(usize, String)> = vec![];
 
-        assert!(!grid.is_empty(), "Grid must not be empty");
-        assert!(!grid[0].is_empty(), "Grid rows must not be empty");
-        assert_eq!(
-            *grid.iter().flat_map(|row| row.iter()).min().unwrap(),
-            0,
-            "Min color value must be 0"
-        );
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| ParseError::Io(err.to_string()))?;
+
+            if line.trim().is_empty() {
+                if !grid.is_empty() {
+                    blank.push((idx, line));
+                }
+                continue;
+            }
+
+            if grid.is_empty() {
+                first_line = idx + 1;
+            }
+            for (idx, line) in blank.drain(..) {
+                grid.push(parse_row(&line, idx + 1)?);
+            }
+            grid.push(parse_row(&line, idx + 1)?);
+        }
+
+        Self::from_grid(grid, first_line)
+    }
+
+    /// Construct all problem instances from a reader
+    ///
+    /// Instances are encoded like in [Problem::from_reader] and separated by one or more blank
+    /// lines.
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::problem::Problem;
+    ///
+    /// let instances = Problem::read_all("010\n102\n\n01\n10\n".as_bytes()).unwrap();
+    /// assert_eq!(instances.len(), 2);
+    /// assert_eq!(instances[1].height(), 2);
+    /// ```
+    pub fn read_all<R: BufRead>(reader: R) -> Result<Vec<Self>, ParseError> {
+        let mut instances: Vec<Problem> = vec![];
+        let mut grid: Vec<Vec<Color>> = vec![];
+        // line number (1-based) of the current instance's first row
+        let mut first_line = 0;
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| ParseError::Io(err.to_string()))?;
+
+            if line.trim().is_empty() {
+                if !grid.is_empty() {
+                    instances.push(Self::from_grid(std::mem::take(&mut grid), first_line)?);
+                }
+                continue;
+            }
+
+            if grid.is_empty() {
+                first_line = idx + 1;
+            }
+
+            grid.push(parse_row(&line, idx + 1)?);
+        }
+
+        if !grid.is_empty() || instances.is_empty() {
+            instances.push(Self::from_grid(grid, first_line)?);
+        }
+
+        Ok(instances)
+    }
+
+    /// Validates a parsed grid, whose first row was read from line `first_line`
+    fn from_grid(grid: Vec<Vec<Color>>, first_line: usize) -> Result<Self, ParseError> {
+        let width = grid.first().ok_or(ParseError::Empty)?.len();
+
+        for (idx, row) in grid.iter().enumerate() {
+            if row.len() != width {
+                return Err(ParseError::RaggedRow {
+                    line: first_line + idx,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+        }
+
+        let mut used = [false; 10];
+        for color in grid.iter().flatten() {
+            used[*color as usize] = true;
+        }
+        let num_colors = used.iter().rposition(|used| *used).unwrap() + 1;
+        if let Some(color) = used[..num_colors].iter().position(|used| !used) {
+            return Err(ParseError::MissingColor {
+                color: color as Color,
+                num_colors,
+            });
+        }
+
+        Ok(Self { grid })
+    }
+
+    /// Encodes the problem in the format read by [Problem::from_reader], including a trailing newline
+    pub fn to_ascii(&self) -> String {
+        let mut s = String::with_capacity(self.height() * (self.width() + 1));
+        for row in self.grid.iter() {
+            for color in row {
+                s.push(char::from(b'0' + *color));
+            }
+            s.push('\n');
+        }
+        s
+    }
 
-        Self { grid }
+    /// A stable 64 bit hash (FNV-1a) of the problem's [ASCII encoding](Problem::to_ascii)
+    ///
+    /// Used to match stored solutions with the instance they solve.
+    pub fn fingerprint(&self) -> u64 {
+        self.to_ascii()
+            .bytes()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
     }
 
     /// The problem's height
@@ -78,8 +264,22 @@ impl Problem {
         *self.grid.iter().flat_map(|row| row.iter()).max().unwrap() as usize + 1
     }
 
-    /// Colors a problem instance with the given color
-    pub fn apply_color(&mut self, color: Color) {
+    /// Returns all tiles connected to (0,0) by tiles of the same color
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::problem::Problem;
+    ///
+    /// // the region reaches the last but one column of a board wider than 255 tiles
+    /// let grid = format!("{}1\n{}", "0".repeat(299), "1".repeat(300));
+    /// let instance: Problem = grid.parse().unwrap();
+    /// let region = instance.flooded_region();
+    /// assert_eq!(region.len(), 299);
+    /// assert!(region.contains(&(0, 298)));
+    /// assert!(!region.contains(&(0, 299)));
+    /// assert!(!region.contains(&(1, 0)));
+    /// ```
+    pub fn flooded_region(&self) -> HashSet<Point> {
         let curr_color = self.grid[0][0];
 
         let mut current_cluster: HashSet<Point> = Default::default();
@@ -91,14 +291,181 @@ impl Problem {
             }
 
             for (y, x) in neighbours(y, x, self.height(), self.width()) {
-                if self.grid[y as usize][x as usize] == curr_color {
+                if self.grid[y][x] == curr_color {
                     queue.push((y, x))
                 }
             }
         }
 
-        for (y, x) in current_cluster {
-            self.grid[y as usize][x as usize] = color;
+        current_cluster
+    }
+
+    /// If all tiles have the same color
+    pub fn is_solved(&self) -> bool {
+        let color = self.grid[0][0];
+        self.grid.iter().flatten().all(|c| *c == color)
+    }
+
+    /// Colors a problem instance with the given color
+    pub fn apply_color(&mut self, color: Color) {
+        for (y, x) in self.flooded_region() {
+            self.grid[y][x] = color;
+        }
+    }
+
+    /// Returns a copy of the instance after applying the given moves
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::problem::Problem;
+    ///
+    /// let instance: Problem = "010\n100\n012".parse().unwrap();
+    /// assert_eq!(instance.after(&[1, 0]).to_ascii(), "000\n000\n012\n");
+    /// ```
+    pub fn after(&self, moves: &[Color]) -> Problem {
+        let mut played = self.clone();
+        for color in moves {
+            played.apply_color(*color);
         }
+        played
+    }
+}
+
+/// Parses a single row of digits, which was read from line `line_no`
+///
+/// A trailing carriage return of Windows line endings is ignored.
+fn parse_row(line: &str, line_no: usize) -> Result<Vec<Color>, ParseError> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    line.chars()
+        .enumerate()
+        .map(|(column, ch)| match ch.to_digit(10) {
+            Some(digit) => Ok(digit as Color),
+            None => Err(ParseError::InvalidChar {
+                line: line_no,
+                column: column + 1,
+                ch,
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows_line_endings() {
+        let instances = Problem::read_all("01\r\n10\r\n\r\n2\r\n".as_bytes());
+        assert_eq!(
+            instances.err(),
+            Some(ParseError::MissingColor {
+                color: 0,
+                num_colors: 3
+            })
+        );
+
+        let instances = Problem::read_all("01\r\n10\r\n\r\n10\r\n01\r\n".as_bytes()).unwrap();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[1].to_ascii(), "10\n01\n");
+
+        let instance: Problem = "\r\n012\r\n210\r\n\r\n".parse().unwrap();
+        assert_eq!((instance.height(), instance.width()), (2, 3));
+    }
+
+    #[test]
+    fn trims_whitespace_only_lines() {
+        let instance: Problem = " \t\n01\n10\n".parse().unwrap();
+        assert_eq!(instance.to_ascii(), "01\n10\n");
+
+        let instance: Problem = "01\n10\n \n\t\n".parse().unwrap();
+        assert_eq!(instance.to_ascii(), "01\n10\n");
+    }
+
+    #[test]
+    fn reports_parse_errors_with_their_position() {
+        let parse = |s: &str| s.parse::<Problem>().err();
+
+        assert_eq!(parse(""), Some(ParseError::Empty));
+        assert_eq!(parse("\n\n"), Some(ParseError::Empty));
+        assert_eq!(
+            parse("\n01\n1a\n"),
+            Some(ParseError::InvalidChar {
+                line: 3,
+                column: 2,
+                ch: 'a'
+            })
+        );
+        assert_eq!(
+            parse("\n\n012\n01\n"),
+            Some(ParseError::RaggedRow {
+                line: 4,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            parse("02\n20\n"),
+            Some(ParseError::MissingColor {
+                color: 1,
+                num_colors: 3
+            })
+        );
+        // blank lines inside a single instance are rows of length 0
+        assert_eq!(
+            parse("01\n\n10\n"),
+            Some(ParseError::RaggedRow {
+                line: 2,
+                expected: 2,
+                found: 0
+            })
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors_of_later_instances() {
+        let err = Problem::read_all("01\n10\n\n\n01\n1\n".as_bytes()).err();
+        assert_eq!(
+            err,
+            Some(ParseError::RaggedRow {
+                line: 6,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Problem::read_all("\n\n".as_bytes()).err(),
+            Some(ParseError::Empty)
+        );
+    }
+
+    #[test]
+    fn round_trips_ascii() {
+        let ascii = "0120\n2201\n1021\n";
+        let instance: Problem = ascii.parse().unwrap();
+        assert_eq!(instance.to_ascii(), ascii);
+        assert_eq!(
+            instance.fingerprint(),
+            ascii.parse::<Problem>().unwrap().fingerprint()
+        );
+        assert_ne!(
+            instance.fingerprint(),
+            "0120\n2201\n1012\n"
+                .parse::<Problem>()
+                .unwrap()
+                .fingerprint()
+        );
+    }
+
+    #[test]
+    fn reports_carriage_returns_inside_rows() {
+        let err = "0\r1\n10\n".parse::<Problem>().err();
+        assert_eq!(
+            err,
+            Some(ParseError::InvalidChar {
+                line: 1,
+                column: 2,
+                ch: '\r'
+            })
+        );
     }
 }
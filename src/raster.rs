@@ -0,0 +1,277 @@
+//! Rendering boards and solutions as PNG images and animated GIFs
+
+use std::{io::Write, time::Duration};
+
+use crate::{colorizer::Colorizer, problem::Problem, solution::Solution};
+
+/// Palette index of tile outlines
+const OUTLINE: u8 = 10;
+/// Palette index of the flooded region's border
+const BORDER: u8 = 11;
+/// Number of palette entries, a power of two as required by GIF
+const PALETTE_SIZE: usize = 16;
+
+/// Appearance of rendered images
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    /// Edge length of a single tile in pixels
+    pub tile_size: u32,
+    /// Draw a thin outline around every tile
+    pub outlines: bool,
+    /// Draw a thick border around the flooded region
+    pub highlight_flooded: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            tile_size: 24,
+            outlines: true,
+            highlight_flooded: true,
+        }
+    }
+}
+
+/// A rendered board, stored as indices into [palette]
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major palette indices, one per pixel
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Draws lines of `thickness` pixels along the inner edges of tile `(y, x)`, skipping every
+    /// edge towards a neighbour `(dy, dx)` for which `skip` returns true
+    fn outline_tile<F>(
+        &mut self,
+        (y, x): (usize, usize),
+        tile: usize,
+        thickness: usize,
+        index: u8,
+        skip: F,
+    ) where
+        F: Fn(isize, isize) -> bool,
+    {
+        let (top, left) = (y * tile, x * tile);
+        let thickness = thickness.min(tile);
+        let edges = [
+            ((-1, 0), (top, left, thickness, tile)),
+            ((1, 0), (top + tile - thickness, left, thickness, tile)),
+            ((0, -1), (top, left, tile, thickness)),
+            ((0, 1), (top, left + tile - thickness, tile, thickness)),
+        ];
+
+        for ((dy, dx), (top, left, height, width)) in edges {
+            if skip(dy, dx) {
+                continue;
+            }
+            for row in top..top + height {
+                let offset = row * self.width as usize + left;
+                self.pixels[offset..offset + width].fill(index);
+            }
+        }
+    }
+}
+
+/// RGB values of all palette indices used by [Frame]s
+///
+/// The first ten entries are the colors used by the terminal output.
+pub fn palette() -> Vec<u8> {
+    let colorizer = Colorizer::new();
+    let mut palette = Vec::with_capacity(3 * PALETTE_SIZE);
+    for color in 0..10 {
+        let (r, g, b) = colorizer.rgb(color);
+        palette.extend([r, g, b]);
+    }
+    palette.extend([64, 64, 64]);
+    palette.extend([0, 0, 0]);
+    palette.resize(3 * PALETTE_SIZE, 255);
+    palette
+}
+
+/// Renders a single board
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{problem::Problem, raster::{render_frame, RasterOptions}};
+///
+/// let instance: Problem = "010\n100".parse().unwrap();
+/// let options = RasterOptions { tile_size: 10, ..Default::default() };
+/// let frame = render_frame(&instance, &options);
+/// assert_eq!((frame.width, frame.height), (30, 20));
+/// assert_eq!(frame.pixels.len(), 600);
+/// ```
+pub fn render_frame(instance: &Problem, options: &RasterOptions) -> Frame {
+    let tile = options.tile_size as usize;
+    let (width, height) = (instance.width() * tile, instance.height() * tile);
+    let mut pixels = vec![0; width * height];
+
+    for (y, row) in instance.grid.iter().enumerate() {
+        for (x, color) in row.iter().enumerate() {
+            for dy in 0..tile {
+                let offset = (y * tile + dy) * width + x * tile;
+                pixels[offset..offset + tile].fill(*color);
+            }
+        }
+    }
+
+    let mut frame = Frame {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    };
+
+    if options.outlines && tile > 2 {
+        for (y, row) in instance.grid.iter().enumerate() {
+            for x in 0..row.len() {
+                frame.outline_tile((y, x), tile, 1, OUTLINE, |_, _| false);
+            }
+        }
+    }
+
+    if options.highlight_flooded {
+        let region = instance.flooded_region();
+        let thickness = (tile / 8).max(1);
+        let contains =
+            |y: isize, x: isize| y >= 0 && x >= 0 && region.contains(&(y as usize, x as usize));
+        for &(y, x) in region.iter() {
+            frame.outline_tile((y, x), tile, thickness, BORDER, |dy, dx| {
+                contains(y as isize + dy, x as isize + dx)
+            });
+        }
+    }
+
+    frame
+}
+
+/// Renders the initial board and the board after every move of a solution
+pub fn render_frames(
+    instance: &Problem,
+    solution: &Solution,
+    options: &RasterOptions,
+) -> Vec<Frame> {
+    let mut instance = instance.clone();
+    let mut frames = vec![render_frame(&instance, options)];
+    for color in solution.colors.iter() {
+        instance.apply_color(*color);
+        frames.push(render_frame(&instance, options));
+    }
+    frames
+}
+
+/// Writes a frame as indexed PNG image
+pub fn write_png<W: Write>(w: W, frame: &Frame) -> std::io::Result<()> {
+    let mut encoder = png::Encoder::new(w, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette());
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&frame.pixels))
+        .map_err(std::io::Error::other)
+}
+
+/// Writes frames as endlessly looping animated GIF, showing every frame for `delay`
+///
+/// Fails with [InvalidInput] if there are no frames, frames differ in size or are larger than
+/// 65535 pixels in any dimension.
+///
+/// [InvalidInput]: std::io::ErrorKind::InvalidInput
+///
+/// # Example
+/// ```
+/// use std::{io::ErrorKind, time::Duration};
+/// use color_flood_rs::raster::{write_gif, Frame};
+///
+/// let frame = Frame { width: 70000, height: 1, pixels: vec![0; 70000] };
+/// let err = write_gif(vec![], &[frame], Duration::from_millis(500)).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::InvalidInput);
+/// ```
+pub fn write_gif<W: Write>(w: W, frames: &[Frame], delay: Duration) -> std::io::Result<()> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidInput, msg);
+
+    let first = frames
+        .first()
+        .ok_or_else(|| invalid("GIF must have at least one frame"))?;
+    let (width, height) = match (u16::try_from(first.width), u16::try_from(first.height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => {
+            return Err(invalid(&format!(
+                "GIF frames must not be larger than 65535x65535 pixels, got {}x{}",
+                first.width, first.height
+            )))
+        }
+    };
+    if frames
+        .iter()
+        .any(|frame| (frame.width, frame.height) != (first.width, first.height))
+    {
+        return Err(invalid("All frames must have the same size"));
+    }
+    // GIF delays are given in hundredths of a second
+    let delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+
+    let mut encoder =
+        gif::Encoder::new(w, width, height, &palette()).map_err(std::io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(std::io::Error::other)?;
+
+    for frame in frames {
+        let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, &*frame.pixels, None);
+        gif_frame.delay = delay;
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(std::io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::*;
+
+    fn frame(width: u32, height: u32) -> Frame {
+        Frame {
+            width,
+            height,
+            pixels: vec![0; (width * height) as usize],
+        }
+    }
+
+    #[test]
+    fn rejects_gifs_without_frames() {
+        let err = write_gif(vec![], &[], Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn rejects_frames_of_different_sizes() {
+        let err = write_gif(vec![], &[frame(2, 2), frame(2, 3)], Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn rejects_frames_taller_than_gifs_allow() {
+        let err = write_gif(vec![], &[frame(1, 65536)], Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn writes_every_frame() {
+        let instance: Problem = "01\n10".parse().unwrap();
+        let frames = render_frames(&instance, &Solution::from([1, 0]), &Default::default());
+        assert_eq!(frames.len(), 3);
+
+        let mut gif = vec![];
+        write_gif(&mut gif, &frames, Duration::from_millis(100)).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        // every frame starts with a graphic control extension
+        assert_eq!(gif.windows(2).filter(|w| w == b"\x21\xf9").count(), 3);
+    }
+}
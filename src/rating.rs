@@ -0,0 +1,242 @@
+//! Rating the difficulty of problem instances
+
+use std::cmp::Reverse;
+
+use serde::Serialize;
+
+use crate::{
+    game::GameState,
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+    solution::Solution,
+};
+
+/// Structural features of an instance that determine its difficulty
+#[derive(Debug, Clone, Serialize)]
+pub struct Features {
+    /// Number of clusters
+    pub clusters: usize,
+    /// Max distance between any two clusters in the cluster adjacency graph
+    pub diameter: usize,
+    /// Normalized entropy of the tile count per color in `[0, 1]`, where 1 means all colors
+    /// occur equally often
+    pub color_balance: f64,
+    /// Length of the [greedy] solution
+    pub greedy_length: usize,
+    /// Length of an optimal solution
+    pub optimum: usize,
+    /// Number of distinct optimal solutions, possibly capped
+    pub optimal_solutions: usize,
+}
+
+impl Features {
+    /// Computes the structural features of an instance
+    ///
+    /// `optimum` and `optimal_solutions` can't be derived from the structure and must be obtained
+    /// by solving the instance. The [diameter](ClusterGraph::diameter) takes time quadratic in the
+    /// number of clusters, which is still small compared to solving.
+    pub fn from_problem(instance: &Problem, optimum: usize, optimal_solutions: usize) -> Self {
+        let graph = ClusterGraph::from_problem(instance);
+
+        Self {
+            clusters: graph.len(),
+            diameter: graph.diameter(),
+            color_balance: color_balance(instance),
+            greedy_length: greedy(instance).colors.len(),
+            optimum,
+            optimal_solutions,
+        }
+    }
+}
+
+/// Difficulty tier of an instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Difficulty rating of an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct Rating {
+    /// Score in `[0, 100]`, higher is harder
+    pub score: f64,
+    pub tier: Tier,
+}
+
+/// Rates the difficulty of an instance by its features
+///
+/// The score is a weighted sum of the following terms, each scaled to `[0, 1]`:
+/// - `0.25`: number of clusters, saturating for large boards
+/// - `0.20`: diameter of the cluster graph, saturating for large boards
+/// - `0.15`: color balance
+/// - `0.25`: relative number of moves the greedy solution wastes, as this means that looking
+///   ahead is required
+/// - `0.15`: uniqueness of the optimal solution, as few optimal solutions are hard to find
+///
+/// Scores below 35 are rated [Tier::Easy], scores below 60 [Tier::Medium] and all others
+/// [Tier::Hard].
+pub fn rate(features: &Features) -> Rating {
+    let size = 1.0 - (-(features.clusters as f64) / 200.0).exp();
+    let depth = 1.0 - (-(features.diameter as f64) / 20.0).exp();
+    let greedy_gap = if features.optimum == 0 {
+        0.0
+    } else {
+        let wasted = features.greedy_length.saturating_sub(features.optimum) as f64;
+        (2.0 * wasted / features.optimum as f64).min(1.0)
+    };
+    let uniqueness = 1.0 / features.optimal_solutions.max(1) as f64;
+
+    let score = 100.0
+        * (0.25 * size
+            + 0.20 * depth
+            + 0.15 * features.color_balance
+            + 0.25 * greedy_gap
+            + 0.15 * uniqueness);
+
+    let tier = match score {
+        s if s < 35.0 => Tier::Easy,
+        s if s < 60.0 => Tier::Medium,
+        _ => Tier::Hard,
+    };
+
+    Rating { score, tier }
+}
+
+/// Solves an instance by always choosing the color that floods the most tiles
+///
+/// Ties are broken by the smaller color.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{problem::Problem, rating::greedy};
+///
+/// let mut instance: Problem = "010\n100\n012".parse().unwrap();
+/// let solution = greedy(&instance);
+/// for color in solution.colors.iter() {
+///     instance.apply_color(*color);
+/// }
+/// assert!(instance.is_solved());
+/// ```
+pub fn greedy(instance: &Problem) -> Solution {
+    let graph = ClusterGraph::from_problem(instance);
+    let mut game = GameState::new(&graph);
+    let mut colors: Vec<Color> = vec![];
+
+    while !game.is_solved() {
+        let color = game
+            .candidates()
+            .into_iter()
+            .min_by_key(|color| Reverse(game.gain(*color)))
+            .expect("Unsolved games have clusters adjacent to the flooded region");
+
+        game.play(color);
+        colors.push(color);
+    }
+
+    Solution::from(colors)
+}
+
+/// Normalized entropy of the color distribution
+fn color_balance(instance: &Problem) -> f64 {
+    let num_colors = instance.num_colors();
+    if num_colors < 2 {
+        return 1.0;
+    }
+
+    let mut counts = vec![0usize; num_colors];
+    for color in instance.grid.iter().flatten() {
+        counts[*color as usize] += 1;
+    }
+
+    let total = (instance.height() * instance.width()) as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total;
+            -p * p.ln()
+        })
+        .sum();
+
+    entropy / (num_colors as f64).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(clusters: usize, diameter: usize, greedy_length: usize) -> Features {
+        Features {
+            clusters,
+            diameter,
+            color_balance: 1.0,
+            greedy_length,
+            optimum: 10,
+            optimal_solutions: 4,
+        }
+    }
+
+    #[test]
+    fn rates_tiers_by_score() {
+        let easy = Features {
+            color_balance: 0.0,
+            optimal_solutions: 100,
+            ..features(1, 0, 10)
+        };
+        let rating = rate(&easy);
+        assert!(rating.score < 1.0);
+        assert_eq!(rating.tier, Tier::Easy);
+
+        let rating = rate(&features(200, 20, 10));
+        assert!((35.0..60.0).contains(&rating.score));
+        assert_eq!(rating.tier, Tier::Medium);
+
+        let hard = Features {
+            optimal_solutions: 1,
+            ..features(1000, 60, 20)
+        };
+        let rating = rate(&hard);
+        assert!(rating.score > 95.0 && rating.score <= 100.0);
+        assert_eq!(rating.tier, Tier::Hard);
+    }
+
+    #[test]
+    fn wasted_greedy_moves_raise_the_score() {
+        let scores: Vec<f64> = [10, 12, 14, 15]
+            .into_iter()
+            .map(|greedy_length| rate(&features(50, 8, greedy_length)).score)
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // the gap saturates at half of the optimum
+        assert_eq!(scores[3], rate(&features(50, 8, 30)).score);
+        // an unknown optimum doesn't count as a gap
+        let unknown = Features {
+            optimum: 0,
+            ..features(50, 8, 30)
+        };
+        assert_eq!(rate(&unknown).score, scores[0]);
+    }
+
+    #[test]
+    fn measures_color_balance() {
+        let balanced: Problem = "01\n10\n".parse().unwrap();
+        assert!((color_balance(&balanced) - 1.0).abs() < 1e-9);
+        let single: Problem = "00\n00\n".parse().unwrap();
+        assert_eq!(color_balance(&single), 1.0);
+        let skewed: Problem = "0000\n0001\n".parse().unwrap();
+        assert!(color_balance(&skewed) < 0.6);
+    }
+
+    #[test]
+    fn greedy_breaks_ties_by_the_smaller_color() {
+        // colors 1 and 2 both flood a single tile first
+        let instance: Problem = "01\n20\n".parse().unwrap();
+        assert_eq!(greedy(&instance).colors, vec![1, 0, 2]);
+        let solved: Problem = "00\n00\n".parse().unwrap();
+        assert!(greedy(&solved).colors.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! Machine-readable reports of all subcommands
+
+use serde::Serialize;
+
+use crate::{
+    analysis::MoveEvaluation,
+    cluster::Cluster,
+    problem::{Color, Problem},
+    rating::{Features, Rating},
+};
+
+/// Metadata of a problem instance
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceInfo {
+    /// Name of the instance, usually its file path
+    pub name: String,
+    pub height: usize,
+    pub width: usize,
+    pub colors: usize,
+    pub clusters: usize,
+}
+
+impl InstanceInfo {
+    pub fn new(name: &str, instance: &Problem) -> Self {
+        Self {
+            name: name.to_owned(),
+            height: instance.height(),
+            width: instance.width(),
+            colors: instance.num_colors(),
+            clusters: Cluster::from_problem(instance).len(),
+        }
+    }
+}
+
+/// Solution bounds a search started with
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Bounds {
+    pub lower: usize,
+    pub upper: usize,
+}
+
+/// A single z3 run of a search
+#[derive(Debug, Clone, Serialize)]
+pub struct AttemptReport {
+    /// Solution length z3 was run with
+    pub t: usize,
+    pub result: &'static str,
+    /// Time z3 spent checking
+    pub time_secs: f64,
+    /// Time spent building the encoding
+    pub encoding_secs: f64,
+    /// Number of assertions of the encoding
+    pub asserts: usize,
+}
+
+/// Report of solving a single instance
+#[derive(Debug, Clone, Serialize)]
+pub struct SolveReport {
+    pub instance: InstanceInfo,
+    /// Name of the z3 strategy, or `heuristic-search` for [search](crate::search::search)
+    pub strategy: &'static str,
+    /// Moves played before solving. The solution continues them
+    pub prefix: Vec<Color>,
+    /// Missing on dry runs
+    pub bounds: Option<Bounds>,
+    pub attempts: Vec<AttemptReport>,
+    /// Result of the last z3 run (or the last satisfiable one, if any). Missing on dry runs
+    pub result: Option<&'static str>,
+    /// The shortest color sequence found
+    pub solution: Option<Vec<Color>>,
+    /// If the solution is proven to be of minimal length
+    pub optimal: bool,
+}
+
+/// A problem instance's grid, one string of digits per row
+#[derive(Debug, Clone, Serialize)]
+pub struct BoardReport {
+    /// Seed the board was generated with
+    pub seed: Option<u64>,
+    pub height: usize,
+    pub width: usize,
+    pub colors: usize,
+    pub grid: Vec<String>,
+}
+
+impl BoardReport {
+    pub fn new(instance: &Problem, seed: Option<u64>) -> Self {
+        Self {
+            seed,
+            height: instance.height(),
+            width: instance.width(),
+            colors: instance.num_colors(),
+            grid: instance.to_ascii().lines().map(str::to_owned).collect(),
+        }
+    }
+}
+
+/// Report of a designed board
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignReport {
+    pub board: BoardReport,
+    pub optimum: usize,
+    /// Number of optimal solutions, if counted
+    pub optimal_solutions: Option<usize>,
+    /// Number of boards evaluated during the search
+    pub evaluated: usize,
+}
+
+/// Report of rating a single instance
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingReport {
+    pub instance: InstanceInfo,
+    pub features: Features,
+    pub rating: Rating,
+}
+
+/// Report of evaluating every possible next move of an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisReport {
+    pub instance: InstanceInfo,
+    /// Moves played before the evaluated position
+    pub prefix: Vec<Color>,
+    /// Evaluations ordered from the best to the worst move
+    pub moves: Vec<MoveEvaluation>,
+}
+
+/// Report of checking whether a sequence of moves solves an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub instance: InstanceInfo,
+    /// Number of moves checked
+    pub moves: usize,
+    /// If the instance is solved after the moves
+    pub solved: bool,
+}
+
+/// A node of an instance's cluster graph
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNodeReport {
+    pub color: Color,
+    /// Number of tiles of the node's cluster
+    pub size: usize,
+    /// Distance from the start node
+    pub distance: usize,
+    /// Ids of the adjacent nodes, i.e. their indices in the list of nodes
+    pub neighbours: Vec<usize>,
+}
+
+/// Report of an instance's cluster graph
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphReport {
+    pub instance: InstanceInfo,
+    /// Moves played before building the graph
+    pub prefix: Vec<Color>,
+    /// Id of the node containing the top left tile
+    pub start: usize,
+    pub edges: usize,
+    /// Max distance from the start node to any node
+    pub eccentricity: usize,
+    /// Max distance between any two nodes, only computed on request
+    pub diameter: Option<usize>,
+    pub nodes: Vec<GraphNodeReport>,
+}
+
+/// A single step of a replayed solution
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStepReport {
+    /// Number of moves made, 0 for the initial board
+    pub step: usize,
+    /// Color chosen in this step, missing for the initial board
+    pub color: Option<Color>,
+    /// The board after this step, one string of digits per row
+    pub grid: Vec<String>,
+}
+
+/// Lowercase name of a z3 result
+#[cfg(feature = "z3")]
+pub fn result_name(result: z3::SatResult) -> &'static str {
+    match result {
+        z3::SatResult::Sat => "sat",
+        z3::SatResult::Unsat => "unsat",
+        z3::SatResult::Unknown => "unknown",
+    }
+}
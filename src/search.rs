@@ -0,0 +1,231 @@
+//! Solving problem instances by heuristic search, without z3
+//!
+//! The board is reduced to its cluster graph, where a state is the set of flooded clusters and
+//! a move adds all clusters of the chosen color that are adjacent to the flooded ones. States are
+//! expanded best-first ([A*]) ordered by the number of moves made plus a lower bound of the moves
+//! still needed, which is the max of
+//! - the number of colors not yet flooded, as every move floods at most one color completely
+//! - the distance from the flooded clusters to the farthest cluster in the cluster graph, as
+//!   every move advances the flooded region by at most one cluster
+//!
+//! Both bounds change by at most one per move, so the first solved state found is optimal. A move
+//! that floods the last remaining clusters of a color is always played right away, as no other
+//! move can be better.
+//!
+//! [A*]: https://en.wikipedia.org/wiki/A*_search_algorithm
+
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    time::Instant,
+};
+
+use crate::{
+    cancel::CancelHandle,
+    game::GameState,
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+    rating::greedy,
+    solution::Solution,
+};
+
+/// Limits of a search
+///
+/// A search that exceeds its limits or is cancelled returns the [greedy] solution, which is not
+/// necessarily minimal.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Point in time after which the search gives up
+    pub deadline: Option<Instant>,
+    /// Max number of states to expand
+    pub max_expansions: Option<usize>,
+    /// Stops the search early when cancelled, like an exceeded limit
+    pub cancel: Option<CancelHandle>,
+}
+
+/// Result of a search
+#[derive(Clone)]
+pub struct SearchOutcome {
+    pub solution: Solution,
+    /// If the solution is proven to be of minimal length
+    pub optimal: bool,
+    /// Number of states expanded
+    pub expanded: usize,
+}
+
+/// Set of flooded clusters, one bit per cluster
+///
+/// States are stored in this compact form and replayed as a [GameState] when expanded.
+type State = Vec<u64>;
+
+fn to_state(game: &GameState) -> State {
+    let mut state: State = vec![0; game.graph().len().div_ceil(64)];
+    for idx in (0..game.graph().len()).filter(|idx| game.is_flooded(*idx)) {
+        state[idx / 64] |= 1 << (idx % 64);
+    }
+    state
+}
+
+/// Lower bound of the number of moves needed to flood all clusters
+fn lower_bound(game: &GameState) -> usize {
+    let graph = game.graph();
+    let mut distances = vec![usize::MAX; graph.len()];
+    let mut queue = VecDeque::new();
+    for idx in (0..graph.len()).filter(|idx| game.is_flooded(*idx)) {
+        distances[idx] = 0;
+        queue.push_back(idx);
+    }
+
+    let mut eccentricity = 0;
+    while let Some(idx) = queue.pop_front() {
+        eccentricity = eccentricity.max(distances[idx]);
+        for &neighbour in graph.neighbours(idx).iter() {
+            if distances[neighbour] == usize::MAX {
+                distances[neighbour] = distances[idx] + 1;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    game.remaining_colors().max(eccentricity)
+}
+
+/// All moves worth considering from a state along with the resulting states
+fn successors<'g>(game: &GameState<'g>) -> Vec<(Color, GameState<'g>)> {
+    let play = |color: Color| {
+        let mut next = game.clone();
+        next.play(color);
+        (color, next)
+    };
+
+    let candidates = game.candidates();
+    // flooding the last clusters of a color never hurts
+    if let Some(color) = candidates
+        .iter()
+        .find(|color| game.frontier(**color).len() == game.remaining(**color))
+    {
+        return vec![play(*color)];
+    }
+
+    candidates.into_iter().map(play).collect()
+}
+
+/// A state reached during the search and the move that reached it
+struct Node {
+    state: State,
+    parent: Option<usize>,
+    color: Color,
+}
+
+/// Colors played from the initial state to reach a node
+fn path(nodes: &[Node], mut idx: usize) -> Vec<Color> {
+    let mut colors = vec![];
+    while let Some(parent) = nodes[idx].parent {
+        colors.push(nodes[idx].color);
+        idx = parent;
+    }
+    colors.reverse();
+    colors
+}
+
+/// Finds a minimal solution of an instance, or the [greedy] one if the search exceeds its limits
+///
+/// The instance may be partially played already, in which case its flooded region is the area
+/// of the top left tile's color.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{problem::Problem, search::{search, SearchOptions}};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let outcome = search(&instance, &SearchOptions::default());
+/// assert_eq!(outcome.solution.colors.len(), 4);
+/// assert!(outcome.optimal);
+/// ```
+pub fn search(instance: &Problem, options: &SearchOptions) -> SearchOutcome {
+    let fallback = greedy(instance);
+    let graph = ClusterGraph::from_problem(instance);
+    let initial = GameState::new(&graph);
+
+    let mut nodes = vec![Node {
+        state: to_state(&initial),
+        parent: None,
+        color: 0,
+    }];
+    let mut best_moves: HashMap<State, usize> = HashMap::from([(to_state(&initial), 0)]);
+    // ordered by the estimated total, then by the most moves made to dive towards solutions
+    let mut queue = BinaryHeap::from([(Reverse(lower_bound(&initial)), 0, 0)]);
+
+    let mut expanded = 0;
+    while let Some((Reverse(estimate), moves, idx)) = queue.pop() {
+        if estimate >= fallback.colors.len() {
+            break;
+        }
+        if best_moves.get(&nodes[idx].state) != Some(&moves) {
+            continue;
+        }
+
+        let limit_reached = options
+            .max_expansions
+            .is_some_and(|max_expansions| expanded >= max_expansions)
+            || options
+                .cancel
+                .as_ref()
+                .is_some_and(CancelHandle::is_cancelled)
+            || (expanded % 1024 == 0
+                && options
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline));
+        if limit_reached {
+            return SearchOutcome {
+                solution: fallback,
+                optimal: false,
+                expanded,
+            };
+        }
+        expanded += 1;
+
+        let mut game = GameState::new(&graph);
+        for color in path(&nodes, idx) {
+            game.play(color);
+        }
+
+        for (color, next) in successors(&game) {
+            if next.is_solved() {
+                let mut colors = path(&nodes, idx);
+                colors.push(color);
+                return SearchOutcome {
+                    solution: Solution::from(colors),
+                    optimal: true,
+                    expanded,
+                };
+            }
+
+            let bound = lower_bound(&next);
+            let state = to_state(&next);
+            match best_moves.entry(state.clone()) {
+                Entry::Occupied(entry) if *entry.get() <= moves + 1 => continue,
+                Entry::Occupied(mut entry) => {
+                    entry.insert(moves + 1);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(moves + 1);
+                }
+            }
+
+            nodes.push(Node {
+                state,
+                parent: Some(idx),
+                color,
+            });
+            queue.push((Reverse(moves + 1 + bound), moves + 1, nodes.len() - 1));
+        }
+    }
+
+    // no solution is shorter than the greedy one
+    SearchOutcome {
+        solution: fallback,
+        optimal: true,
+        expanded,
+    }
+}
@@ -0,0 +1,560 @@
+//! Serving solver operations over a local HTTP JSON API
+//!
+//! All endpoints accept a JSON document via `POST` and respond with the same JSON reports as the
+//! command line's `--format json`:
+//! - `/solve`: `{"grid": ["010", "100"], "strategy": "min", "prefix": [1], "time_limit": 10}`
+//! - `/verify`: `{"grid": ["010", "100"], "moves": [1, 0]}`
+//! - `/analyze`: `{"grid": ["010", "100"], "prefix": [], "time_limit": 10}`
+//! - `/generate`: `{"height": 14, "width": 14, "colors": 6, "seed": 1, "distribution": "uniform"}`
+//!
+//! Boards are given as rows of digits like in [BoardReport]. All keys but `grid`, `moves`,
+//! `height` and `colors` are optional. Errors are reported as `{"error": "..."}`.
+
+use std::{
+    io::Read,
+    panic::AssertUnwindSafe,
+    sync::{mpsc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    analysis,
+    game::GameState,
+    generator::{generate, Distribution, GeneratorOptions},
+    graph::ClusterGraph,
+    problem::{Color, Problem},
+    report::{AnalysisReport, BoardReport, InstanceInfo, SolveReport, VerifyReport},
+};
+
+/// Capacity and limits of a server
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Number of requests handled in parallel
+    pub workers: usize,
+    /// Number of requests waiting for a worker, further requests are rejected
+    pub queue_size: usize,
+    /// Time limit of requests that don't specify one
+    pub default_time_limit: Option<Duration>,
+    /// Upper bound of the time limit requests may specify
+    pub max_time_limit: Option<Duration>,
+    /// Max number of tiles of boards given or generated by requests
+    pub max_tiles: usize,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            workers: 1,
+            queue_size: 16,
+            default_time_limit: None,
+            max_time_limit: None,
+            max_tiles: 1 << 16,
+        }
+    }
+}
+
+/// Max size of a request body in bytes
+const MAX_BODY: u64 = 1 << 20;
+
+/// A handled request, passed to the logger of [serve]
+#[derive(Debug, Clone, Copy)]
+pub struct Access<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    /// HTTP status code of the response
+    pub status: u16,
+}
+
+/// Mode of finding a solution, like the solving subcommands of the same name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Find a minimal solution by binary search in reasonable bounds
+    #[default]
+    Min,
+    /// Use the z3 optimizer to find a minimal solution
+    Opt,
+    /// Find a solution with reasonable large size
+    Solve,
+}
+
+/// A single instance to solve, handed to the solver of [serve]
+#[derive(Clone, Copy)]
+pub struct SolveJob<'a> {
+    /// Name of the instance as given by the request
+    pub name: &'a str,
+    pub instance: &'a Problem,
+    pub strategy: Strategy,
+    /// Moves already played, the solver finds their optimal continuation
+    pub prefix: &'a [Color],
+    pub time_limit: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SolveRequest {
+    #[serde(default)]
+    name: String,
+    grid: Vec<String>,
+    #[serde(default)]
+    strategy: Strategy,
+    #[serde(default)]
+    prefix: Vec<Color>,
+    /// In seconds
+    time_limit: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyRequest {
+    #[serde(default)]
+    name: String,
+    grid: Vec<String>,
+    moves: Vec<Color>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnalyzeRequest {
+    #[serde(default)]
+    name: String,
+    grid: Vec<String>,
+    #[serde(default)]
+    prefix: Vec<Color>,
+    /// In seconds, for all moves together
+    time_limit: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenerateRequest {
+    height: usize,
+    width: Option<usize>,
+    colors: usize,
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    distribution: DistributionName,
+    #[serde(default = "default_cohesion")]
+    cohesion: f64,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DistributionName {
+    #[default]
+    Uniform,
+    Clustered,
+}
+
+fn default_cohesion() -> f64 {
+    0.5
+}
+
+#[derive(Serialize)]
+struct ErrorReport {
+    error: String,
+}
+
+/// A response that failed with an HTTP status code and message
+type Failure = (u16, String);
+
+/// Serves the API on `addr` until the server fails
+///
+/// Requests are handled by `options.workers` threads, each calling `solve` for every instance it
+/// needs to solve. Requests that arrive while all workers are busy wait in a queue of
+/// `options.queue_size` requests; once it is full, requests are rejected with status 503. A
+/// request whose handling panics is answered with status 500, and its worker carries on with the
+/// next request. Every response is passed to `log`.
+pub fn serve<F, L>(addr: &str, options: &ServerOptions, solve: F, log: L) -> std::io::Result<()>
+where
+    F: Fn(&SolveJob) -> SolveReport + Sync,
+    L: Fn(&Access) + Sync,
+{
+    let server = Server::http(addr).map_err(std::io::Error::other)?;
+    let (queue, jobs) = mpsc::sync_channel::<Request>(options.queue_size);
+    let jobs = Mutex::new(jobs);
+
+    std::thread::scope(|scope| {
+        for _ in 0..options.workers.max(1) {
+            scope.spawn(|| loop {
+                let Ok(request) = jobs.lock().unwrap().recv() else {
+                    return;
+                };
+                serve_request(request, options, &solve, &log);
+            });
+        }
+
+        for request in server.incoming_requests() {
+            if let Err(mpsc::TrySendError::Full(request)) = queue.try_send(request) {
+                let (method, url) = (request.method().to_string(), request.url().to_owned());
+                let status = respond(
+                    request,
+                    Err((503, "Queue is full, try again later".to_owned())),
+                );
+                log(&Access {
+                    method: &method,
+                    url: &url,
+                    status,
+                });
+            }
+        }
+        drop(queue);
+    });
+
+    Err(std::io::Error::other(
+        "Server stopped accepting connections",
+    ))
+}
+
+/// Handles a request and logs its response, answering with status 500 if handling panics
+fn serve_request<F, L>(request: Request, options: &ServerOptions, solve: &F, log: &L)
+where
+    F: Fn(&SolveJob) -> SolveReport,
+    L: Fn(&Access),
+{
+    let (method, url) = (request.method().to_string(), request.url().to_owned());
+    // tiny_http answers unanswered requests with status 500 when they are dropped while unwinding
+    let handled = std::panic::catch_unwind(AssertUnwindSafe(|| handle(request, options, solve)));
+    log(&Access {
+        method: &method,
+        url: &url,
+        status: handled.unwrap_or(500),
+    });
+}
+
+/// Dispatches a request to its endpoint and responds to it, returning the response's status
+fn handle<F>(mut request: Request, options: &ServerOptions, solve: &F) -> u16
+where
+    F: Fn(&SolveJob) -> SolveReport,
+{
+    let route = match (request.method(), request.url()) {
+        (Method::Post, url @ ("/solve" | "/verify" | "/analyze" | "/generate")) => url.to_owned(),
+        (_, "/solve" | "/verify" | "/analyze" | "/generate") => {
+            return respond(request, Err((405, "Use POST".to_owned())));
+        }
+        (_, url) => {
+            let message = format!("Unknown endpoint {url}");
+            return respond(request, Err((404, message)));
+        }
+    };
+
+    // read one byte more than allowed to tell whether the body is too large
+    let mut body = String::new();
+    if let Err(err) = request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+    {
+        return respond(request, Err((400, format!("Could not read body: {err}"))));
+    }
+    if body.len() as u64 > MAX_BODY {
+        let message = format!("Request body must not be larger than {MAX_BODY} bytes");
+        return respond(request, Err((413, message)));
+    }
+
+    respond(request, route_body(&route, &body, options, solve))
+}
+
+/// Handles the body of a request to an endpoint
+fn route_body<F>(
+    route: &str,
+    body: &str,
+    options: &ServerOptions,
+    solve: &F,
+) -> Result<String, Failure>
+where
+    F: Fn(&SolveJob) -> SolveReport,
+{
+    match route {
+        "/solve" => parse(body).and_then(|body| solve_board(body, options, solve)),
+        "/verify" => parse(body).and_then(|body| verify_board(body, options)),
+        "/analyze" => parse(body).and_then(|body| analyze_board(body, options, solve)),
+        _ => parse(body).and_then(|body| generate_board(body, options)),
+    }
+}
+
+fn solve_board<F>(
+    request: SolveRequest,
+    options: &ServerOptions,
+    solve: &F,
+) -> Result<String, Failure>
+where
+    F: Fn(&SolveJob) -> SolveReport,
+{
+    let instance = parse_board(&request.grid, options)?;
+    validate_moves(&instance, &request.prefix)?;
+    let report = solve(&SolveJob {
+        name: &request.name,
+        instance: &instance,
+        strategy: request.strategy,
+        prefix: &request.prefix,
+        time_limit: time_limit(request.time_limit, options)?,
+    });
+    Ok(to_json(&report))
+}
+
+fn verify_board(request: VerifyRequest, options: &ServerOptions) -> Result<String, Failure> {
+    let instance = parse_board(&request.grid, options)?;
+    validate_moves(&instance, &request.moves)?;
+    let graph = ClusterGraph::from_problem(&instance);
+    let mut game = GameState::new(&graph);
+    for color in request.moves.iter() {
+        game.play(*color);
+    }
+    Ok(to_json(&VerifyReport {
+        instance: InstanceInfo::new(&request.name, &instance),
+        moves: request.moves.len(),
+        solved: game.is_solved(),
+    }))
+}
+
+fn analyze_board<F>(
+    request: AnalyzeRequest,
+    options: &ServerOptions,
+    solve: &F,
+) -> Result<String, Failure>
+where
+    F: Fn(&SolveJob) -> SolveReport,
+{
+    let instance = parse_board(&request.grid, options)?;
+    validate_moves(&instance, &request.prefix)?;
+    let deadline = time_limit(request.time_limit, options)?.map(|limit| Instant::now() + limit);
+
+    let mut board = instance.clone();
+    for color in request.prefix.iter() {
+        board.apply_color(*color);
+    }
+
+    let moves = analysis::analyze(&board, |next| {
+        let time_limit =
+            deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        if time_limit == Some(Duration::ZERO) {
+            return None;
+        }
+        let report = solve(&SolveJob {
+            name: &request.name,
+            instance: next,
+            strategy: Strategy::Min,
+            prefix: &[],
+            time_limit,
+        });
+        Some((report.solution?.len(), report.optimal))
+    });
+
+    Ok(to_json(&AnalysisReport {
+        instance: InstanceInfo::new(&request.name, &instance),
+        prefix: request.prefix,
+        moves,
+    }))
+}
+
+fn generate_board(request: GenerateRequest, server: &ServerOptions) -> Result<String, Failure> {
+    let options = GeneratorOptions {
+        height: request.height,
+        width: request.width.unwrap_or(request.height),
+        colors: request.colors,
+        seed: request.seed,
+        distribution: match request.distribution {
+            DistributionName::Uniform => Distribution::Uniform,
+            DistributionName::Clustered => Distribution::Clustered {
+                cohesion: request.cohesion,
+            },
+        },
+    };
+
+    if !(1..=10).contains(&options.colors) {
+        return Err((400, "Colors must be in [1, 10]".to_owned()));
+    }
+    let tiles = options
+        .height
+        .checked_mul(options.width)
+        .filter(|tiles| *tiles <= server.max_tiles)
+        .ok_or_else(|| {
+            let message = format!("Grid must not have more than {} tiles", server.max_tiles);
+            (413, message)
+        })?;
+    if tiles == 0 || tiles < options.colors {
+        return Err((400, "Grid must have at least one tile per color".to_owned()));
+    }
+
+    Ok(to_json(&BoardReport::new(
+        &generate(&options),
+        Some(options.seed),
+    )))
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, Failure> {
+    serde_json::from_str(body).map_err(|err| (400, format!("Invalid request: {err}")))
+}
+
+fn parse_board(grid: &[String], options: &ServerOptions) -> Result<Problem, Failure> {
+    let tiles: usize = grid.iter().map(String::len).sum();
+    if tiles > options.max_tiles {
+        let message = format!("Grid must not have more than {} tiles", options.max_tiles);
+        return Err((413, message));
+    }
+    grid.join("\n")
+        .parse()
+        .map_err(|err| (400, format!("Invalid grid: {err}")))
+}
+
+/// Fails if a move uses a color that does not occur in the instance
+fn validate_moves(instance: &Problem, moves: &[Color]) -> Result<(), Failure> {
+    match moves
+        .iter()
+        .find(|color| **color as usize >= instance.num_colors())
+    {
+        Some(color) => Err((400, format!("Color {color} does not occur in the grid"))),
+        None => Ok(()),
+    }
+}
+
+/// Time limit of a request, given in seconds, capped at the server's max time limit
+fn time_limit(
+    requested: Option<f64>,
+    options: &ServerOptions,
+) -> Result<Option<Duration>, Failure> {
+    let requested = match requested {
+        Some(secs) if secs > 0.0 => Some(
+            Duration::try_from_secs_f64(secs)
+                .map_err(|err| (400, format!("Invalid time limit: {err}")))?,
+        ),
+        Some(_) => return Err((400, "Time limit must be positive".to_owned())),
+        None => options.default_time_limit,
+    };
+    Ok(match (requested, options.max_time_limit) {
+        (Some(requested), Some(max)) => Some(requested.min(max)),
+        (requested, max) => requested.or(max),
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("Reports are always serializable")
+}
+
+/// Responds to a request and returns the response's status
+fn respond(request: Request, response: Result<String, Failure>) -> u16 {
+    let (status, body) = match response {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, to_json(&ErrorReport { error })),
+    };
+
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    // the client may have gone away in the meantime, which is not the server's concern
+    let _ = request.respond(response);
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use tiny_http::TestRequest;
+
+    use super::*;
+
+    /// Serves a single `POST` request and returns its status
+    fn post<F>(url: &str, body: &str, options: &ServerOptions, solve: F) -> u16
+    where
+        F: Fn(&SolveJob) -> SolveReport,
+    {
+        let body: &'static str = Box::leak(body.to_owned().into_boxed_str());
+        let request = TestRequest::new()
+            .with_method(Method::Post)
+            .with_path(url)
+            .with_body(body);
+        let status = Cell::new(0);
+        serve_request(request.into(), options, &solve, &|access: &Access| {
+            assert_eq!((access.method, access.url), ("POST", url));
+            status.set(access.status);
+        });
+        status.get()
+    }
+
+    fn unreachable_solver(_: &SolveJob) -> SolveReport {
+        unreachable!("request must be rejected before solving")
+    }
+
+    #[test]
+    fn routes_requests() {
+        let options = ServerOptions::default();
+        let verify = r#"{"grid": ["010", "100", "012"], "moves": [1, 0, 2, 1]}"#;
+        assert_eq!(post("/verify", verify, &options, unreachable_solver), 200);
+        let generate = r#"{"height": 3, "colors": 2}"#;
+        assert_eq!(
+            post("/generate", generate, &options, unreachable_solver),
+            200
+        );
+        assert_eq!(post("/unknown", "{}", &options, unreachable_solver), 404);
+
+        let request = TestRequest::new().with_path("/solve");
+        assert_eq!(handle(request.into(), &options, &unreachable_solver), 405);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let options = ServerOptions::default();
+        let post = |url, body| post(url, body, &options, unreachable_solver);
+        assert_eq!(post("/verify", "not json"), 400);
+        assert_eq!(post("/verify", r#"{"grid": ["012"], "moves": [3]}"#), 400);
+        assert_eq!(post("/solve", r#"{"grid": ["01"], "time_limit": -1}"#), 400);
+        assert_eq!(
+            post("/solve", r#"{"grid": ["01"], "time_limit": 1e300}"#),
+            400
+        );
+        assert_eq!(post("/generate", r#"{"height": 1, "colors": 2}"#), 400);
+    }
+
+    #[test]
+    fn rejects_large_bodies_and_boards() {
+        let options = ServerOptions {
+            max_tiles: 100,
+            ..Default::default()
+        };
+        let post = |url, body: &str| post(url, body, &options, unreachable_solver);
+
+        let body = format!(
+            r#"{{"grid": ["01"], "moves": [], "name": "{}"}}"#,
+            "x".repeat(1 << 20)
+        );
+        assert_eq!(post("/verify", &body), 413);
+
+        let grid = format!(r#"{{"grid": ["{}"], "moves": []}}"#, "01".repeat(51));
+        assert_eq!(post("/verify", &grid), 413);
+        let grid = format!(r#"{{"grid": ["{}"], "moves": []}}"#, "01".repeat(50));
+        assert_eq!(post("/verify", &grid), 200);
+
+        assert_eq!(post("/generate", r#"{"height": 11, "colors": 2}"#), 413);
+        // height * width overflows
+        let generate = format!(r#"{{"height": {}, "width": 2, "colors": 2}}"#, usize::MAX);
+        assert_eq!(post("/generate", &generate), 413);
+    }
+
+    #[test]
+    fn survives_panicking_solvers() {
+        let options = ServerOptions::default();
+        let solve = r#"{"grid": ["010", "100", "012"]}"#;
+        let status = post("/solve", solve, &options, |_| panic!("solver failed"));
+        assert_eq!(status, 500);
+    }
+
+    #[test]
+    fn caps_time_limits() {
+        let options = ServerOptions {
+            default_time_limit: Some(Duration::from_secs(10)),
+            max_time_limit: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let secs = |requested| time_limit(requested, &options).unwrap().unwrap().as_secs();
+        assert_eq!(secs(None), 10);
+        assert_eq!(secs(Some(30.0)), 30);
+        assert_eq!(secs(Some(600.0)), 60);
+    }
+}
@@ -1,6 +1,6 @@
 //! Solution model
 
-use std::fmt::Display;
+use std::{fmt::Display, io::BufRead, str::FromStr};
 
 use crate::{colorizer::Colorizer, problem::Color};
 
@@ -31,3 +31,264 @@ where
         Self { colors }
     }
 }
+
+/// A solution along with metadata, as stored in solution files
+///
+/// Solutions are encoded as `key: value` lines
+/// ```text
+/// instance: 5a0d4c3e8b1f2a97
+/// length: 4
+/// optimal: true
+/// moves: 1012
+/// ```
+/// where `moves` is the color sequence, one digit per move, and is the only required key. The
+/// other keys are the [fingerprint](crate::problem::Problem::fingerprint) of the solved instance
+/// in hex, the number of moves and whether the solution is proven to be of minimal length. Lines
+/// starting with `#` are ignored.
+#[derive(Clone)]
+pub struct SolutionFile {
+    pub solution: Solution,
+    /// Fingerprint of the solved instance
+    pub instance: Option<u64>,
+    /// If the solution is proven to be of minimal length
+    pub optimal: Option<bool>,
+}
+
+impl Display for SolutionFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(instance) = self.instance {
+            writeln!(f, "instance: {instance:016x}")?;
+        }
+        writeln!(f, "length: {}", self.solution.colors.len())?;
+        if let Some(optimal) = self.optimal {
+            writeln!(f, "optimal: {optimal}")?;
+        }
+        f.write_str("moves: ")?;
+        for color in self.solution.colors.iter() {
+            write!(f, "{color}")?;
+        }
+        writeln!(f)
+    }
+}
+
+impl FromStr for SolutionFile {
+    type Err = SolutionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_reader(s.as_bytes())
+    }
+}
+
+/// Reasons why a solution file could not be parsed
+///
+/// Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionParseError {
+    /// The input could not be read
+    Io(String),
+    /// A line is not of the form `key: value`
+    InvalidLine { line: usize },
+    /// A key other than `instance`, `length`, `optimal` and `moves` was found
+    UnknownKey { line: usize, key: String },
+    /// A value could not be parsed
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+    },
+    /// The `moves` key is missing
+    MissingMoves,
+    /// The `length` key differs from the number of moves
+    LengthMismatch { length: usize, moves: usize },
+}
+
+impl Display for SolutionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolutionParseError::Io(err) => write!(f, "could not read input: {err}"),
+            SolutionParseError::InvalidLine { line } => {
+                write!(f, "line {line}: expected 'key: value'")
+            }
+            SolutionParseError::UnknownKey { line, key } => {
+                write!(f, "line {line}: unknown key {key:?}")
+            }
+            SolutionParseError::InvalidValue { line, key, value } => {
+                write!(f, "line {line}: invalid value {value:?} for key {key:?}")
+            }
+            SolutionParseError::MissingMoves => write!(f, "solution must contain 'moves'"),
+            SolutionParseError::LengthMismatch { length, moves } => write!(
+                f,
+                "solution has length {length}, but {moves} moves are given"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolutionParseError {}
+
+impl SolutionFile {
+    /// Construct a solution file from a reader
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::solution::SolutionFile;
+    ///
+    /// let file = SolutionFile::from_reader("length: 3\noptimal: true\nmoves: 102\n".as_bytes());
+    /// let file = file.unwrap();
+    /// assert_eq!(file.solution.colors, vec![1, 0, 2]);
+    /// assert_eq!(file.optimal, Some(true));
+    /// assert_eq!(file.to_string().parse::<SolutionFile>().unwrap().solution.colors, vec![1, 0, 2]);
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, SolutionParseError> {
+        let mut instance = None;
+        let mut length = None;
+        let mut optimal = None;
+        let mut moves = None;
+
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| SolutionParseError::Io(err.to_string()))?;
+            let line_no = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(SolutionParseError::InvalidLine { line: line_no })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid_value = || SolutionParseError::InvalidValue {
+                line: line_no,
+                key: key.to_owned(),
+                value: value.to_owned(),
+            };
+
+            match key {
+                "instance" => {
+                    instance = Some(u64::from_str_radix(value, 16).map_err(|_| invalid_value())?)
+                }
+                "length" => length = Some(value.parse::<usize>().map_err(|_| invalid_value())?),
+                "optimal" => optimal = Some(value.parse::<bool>().map_err(|_| invalid_value())?),
+                "moves" => {
+                    moves = Some(
+                        value
+                            .chars()
+                            .map(|ch| ch.to_digit(10).map(|digit| digit as Color))
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(invalid_value)?,
+                    )
+                }
+                _ => {
+                    return Err(SolutionParseError::UnknownKey {
+                        line: line_no,
+                        key: key.to_owned(),
+                    })
+                }
+            }
+        }
+
+        let moves: Vec<Color> = moves.ok_or(SolutionParseError::MissingMoves)?;
+        if let Some(length) = length.filter(|length| *length != moves.len()) {
+            return Err(SolutionParseError::LengthMismatch {
+                length,
+                moves: moves.len(),
+            });
+        }
+
+        Ok(Self {
+            solution: Solution::from(moves),
+            instance,
+            optimal,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_solution_files() {
+        let files = [
+            SolutionFile {
+                solution: Solution::from([1, 0, 2, 9]),
+                instance: Some(0x5a0d4c3e8b1f2a97),
+                optimal: Some(false),
+            },
+            SolutionFile {
+                solution: Solution::from([]),
+                instance: None,
+                optimal: None,
+            },
+        ];
+
+        for file in files {
+            let parsed: SolutionFile = file.to_string().parse().unwrap();
+            assert_eq!(parsed.solution.colors, file.solution.colors);
+            assert_eq!(parsed.instance, file.instance);
+            assert_eq!(parsed.optimal, file.optimal);
+        }
+    }
+
+    #[test]
+    fn ignores_comments_blank_lines_and_whitespace() {
+        let file: SolutionFile = "# found by hand\n\n  moves :  0110 \ninstance: ff\n"
+            .parse()
+            .unwrap();
+        assert_eq!(file.solution.colors, vec![0, 1, 1, 0]);
+        assert_eq!(file.instance, Some(0xff));
+        assert_eq!(file.optimal, None);
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let parse = |s: &str| s.parse::<SolutionFile>().err();
+
+        assert_eq!(
+            parse("moves: 01\nlength 2\n"),
+            Some(SolutionParseError::InvalidLine { line: 2 })
+        );
+        assert_eq!(
+            parse("colors: 01\n"),
+            Some(SolutionParseError::UnknownKey {
+                line: 1,
+                key: "colors".to_owned()
+            })
+        );
+        assert_eq!(
+            parse("# header\nmoves: 0x1\n"),
+            Some(SolutionParseError::InvalidValue {
+                line: 2,
+                key: "moves".to_owned(),
+                value: "0x1".to_owned()
+            })
+        );
+        assert_eq!(
+            parse("optimal: yes\nmoves: 0\n"),
+            Some(SolutionParseError::InvalidValue {
+                line: 1,
+                key: "optimal".to_owned(),
+                value: "yes".to_owned()
+            })
+        );
+        assert_eq!(
+            parse("instance: xyz\nmoves: 0\n"),
+            Some(SolutionParseError::InvalidValue {
+                line: 1,
+                key: "instance".to_owned(),
+                value: "xyz".to_owned()
+            })
+        );
+        assert_eq!(
+            parse("length: 2\noptimal: true\n"),
+            Some(SolutionParseError::MissingMoves)
+        );
+        assert_eq!(
+            parse("length: 2\nmoves: 012\n"),
+            Some(SolutionParseError::LengthMismatch {
+                length: 2,
+                moves: 3
+            })
+        );
+    }
+}
@@ -2,20 +2,33 @@
 //!
 //! # Example
 //! ```
-//! let instance = Problem::from_stdin();
-//! let solution_length = 20; // need to match the problem
+//! use color_flood_rs::problem::Problem;
+//! use color_flood_rs::solver::{init_solver, run_solver};
+//!
+//! let instance: Problem = "010\n100\n012".parse().unwrap();
+//! let solution_length = 5; // need to match the problem
 //! let optimize = true; // signals to use the internal z3 optimizer
 //! let ctx = z3::Context::new(&Default::default());
-//! let mut solver_state = init_solver::<T>(ctx, &instance, solution_length, optimize);
-//! let Some((result, solution)) = run_solver(solver_state, solution_length);
+//! let solver_state = init_solver::<z3::Optimize>(&ctx, &instance, solution_length, optimize);
+//! let (result, solution) = run_solver(solver_state, solution_length);
 //! ```
 
+use std::{
+    collections::HashSet,
+    ffi::CString,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
 use z3::ast::{Ast, Bool, Int};
 
+pub use crate::cancel::CancelHandle;
+
 use crate::{
-    cluster::Cluster,
+    cluster::{Cluster, Clustering},
     problem::{Color, Problem},
     solution::Solution,
+    util::Point,
 };
 
 /// A generic abstraction over z3 solver strategies
@@ -30,15 +43,23 @@ pub trait Solver<'c> {
     fn check(&self) -> z3::SatResult;
 
     /// Obtains a model that satisfies the assertions. See [z3::Solver::get_model]
-    fn get_model(&self) -> Option<z3::Model>;
+    fn get_model(&self) -> Option<z3::Model<'_>>;
 
     /// Sets an objective to maximize. See [z3::Optimize::maximize]
     fn maximize(&self, objective: &z3::ast::Int);
+
+    /// Why the last check returned `Unknown`. See [z3::Solver::get_reason_unknown]
+    fn reason_unknown(&self) -> Option<String>;
 }
 
 impl<'ctx> Solver<'ctx> for z3::Solver<'ctx> {
+    /// Creates a solver that leaves Ctrl-C to the application, which may cancel it gracefully
     fn new(ctx: &'ctx z3::Context) -> Self {
-        z3::Solver::new(ctx)
+        let solver = z3::Solver::new(ctx);
+        let mut params = z3::Params::new(ctx);
+        params.set_bool("ctrl_c", false);
+        solver.set_params(&params);
+        solver
     }
 
     fn assert(&self, ast: &z3::ast::Bool) {
@@ -49,13 +70,17 @@ impl<'ctx> Solver<'ctx> for z3::Solver<'ctx> {
         self.check()
     }
 
-    fn get_model(&self) -> Option<z3::Model> {
+    fn get_model(&self) -> Option<z3::Model<'_>> {
         self.get_model()
     }
 
     fn maximize(&self, _: &z3::ast::Int) {
         unimplemented!("z3::Solver does not support Solver::maximize")
     }
+
+    fn reason_unknown(&self) -> Option<String> {
+        self.get_reason_unknown()
+    }
 }
 
 impl<'ctx> Solver<'ctx> for z3::Optimize<'ctx> {
@@ -71,13 +96,17 @@ impl<'ctx> Solver<'ctx> for z3::Optimize<'ctx> {
         self.check(&[])
     }
 
-    fn get_model(&self) -> Option<z3::Model> {
+    fn get_model(&self) -> Option<z3::Model<'_>> {
         self.get_model()
     }
 
     fn maximize(&self, objective: &z3::ast::Int) {
         self.maximize(objective)
     }
+
+    fn reason_unknown(&self) -> Option<String> {
+        self.get_reason_unknown()
+    }
 }
 
 /// The collection of used variables for a solving attempt
@@ -100,6 +129,25 @@ impl<'ctx, T> SolverState<'ctx, T> {
     }
 }
 
+/// Optional variations of the SMT encoding built by [init_solver_with]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Encoding {
+    /// Force every move to flood at least one more cluster, until all clusters are flooded
+    ///
+    /// Only applies when optimizing, as solutions of an exact length may contain idle moves.
+    pub force_progress: bool,
+}
+
+/// Sets the seed of z3's random number generators for all solvers created afterwards
+pub fn set_random_seed(seed: u32) {
+    let value = CString::new(seed.to_string()).unwrap();
+    for param in ["smt.random_seed", "sat.random_seed"] {
+        let param = CString::new(param).unwrap();
+        // SAFETY: both arguments are valid, nul-terminated strings that outlive the call
+        unsafe { z3_sys::Z3_global_param_set(param.as_ptr(), value.as_ptr()) };
+    }
+}
+
 /// Try to solve the given [problem instance][Problem] in `t_max` steps
 ///
 /// # Args
@@ -113,6 +161,60 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
     instance: &Problem,
     t_max: usize,
     optimize: bool,
+) -> SolverState<'ctx, T> {
+    init_solver_with(ctx, instance, t_max, optimize, Encoding::default())
+}
+
+/// Like [init_solver], but builds the encoding with the given [Encoding] options
+pub fn init_solver_with<'ctx, T: Solver<'ctx>>(
+    ctx: &'ctx z3::Context,
+    instance: &Problem,
+    t_max: usize,
+    optimize: bool,
+    encoding: Encoding,
+) -> SolverState<'ctx, T> {
+    init_solver_from(
+        ctx,
+        instance,
+        &instance.flooded_region(),
+        t_max,
+        optimize,
+        encoding,
+    )
+}
+
+/// Like [init_solver_with], but continues a game in which some moves were already played
+///
+/// `flooded` is the region flooded by these moves, i.e. the [flooded region] of `instance` after
+/// applying them. Every cluster of `instance` inside this region is flooded at t=0, whereas
+/// [init_solver] only floods the cluster containing (0,0). The solution is the continuation of
+/// the moves played.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::problem::Problem;
+/// use color_flood_rs::solver::{init_solver_from, run_solver, Encoding};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let mut played = instance.clone();
+/// played.apply_color(1);
+///
+/// let ctx = z3::Context::new(&Default::default());
+/// let state = init_solver_from::<z3::Optimize>(
+///     &ctx, &instance, &played.flooded_region(), 5, true, Encoding::default(),
+/// );
+/// let (_, solution) = run_solver(state, 5);
+/// assert_eq!(solution.unwrap().colors.len(), 3);
+/// ```
+///
+/// [flooded region]: Problem::flooded_region
+pub fn init_solver_from<'ctx, T: Solver<'ctx>>(
+    ctx: &'ctx z3::Context,
+    instance: &Problem,
+    flooded: &HashSet<Point>,
+    t_max: usize,
+    optimize: bool,
+    encoding: Encoding,
 ) -> SolverState<'ctx, T> {
     let mut asserts: Vec<z3::ast::Bool<'_>> = Default::default();
 
@@ -140,15 +242,13 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
     }
 
     // FIND CLUSTERS
-    let clusters = Cluster::from_problem(instance);
-    let start_cluster = clusters
-        .iter()
-        .find(|cluster| cluster.fields.contains(&(0, 0)))
-        .unwrap();
-    let start_cluster_idx = clusters
+    let clustering = Clustering::from_problem(instance);
+    let clusters = &clustering.clusters;
+    // clusters are either entirely flooded or not at all
+    let start_flooded: Vec<bool> = clusters
         .iter()
-        .position(|cluster| std::ptr::eq(cluster, start_cluster))
-        .unwrap();
+        .map(|cluster| cluster.fields.iter().any(|point| flooded.contains(point)))
+        .collect();
 
     // INIT FLOODED VARS
     let flooded_vars: Vec<Vec<Bool>> = {
@@ -164,27 +264,22 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
     };
 
     // Force improvement in every step when optimizing - FIXME: seems to make the solver *slower*
-    #[cfg(not)]
-    if optimize {
-        let num_clusters = Int::from_u64(&ctx, clusters.len() as u64);
+    if optimize && encoding.force_progress {
+        let num_clusters = Int::from_u64(ctx, clusters.len() as u64);
+        let count_flooded = |t: usize| {
+            let ints = flooded_vars
+                .iter()
+                .map(|vars| vars[t].ite(&Int::from_u64(ctx, 1), &Int::from_u64(ctx, 0)))
+                .collect::<Vec<_>>();
+            Int::add(ctx, ints.iter().collect::<Vec<_>>().as_slice())
+        };
+
         for t in 0..t_max {
-            let vars_t = flooded_vars.iter().map(|vars| &vars[t]);
-            let vars_t_plus_1 = flooded_vars.iter().map(|vars| &vars[t + 1]);
-            let sum_t = {
-                let ints = vars_t
-                    .map(|flooded| flooded.ite(&Int::from_u64(&ctx, 1), &Int::from_u64(&ctx, 0)))
-                    .collect::<Vec<_>>();
-                Int::add(&ctx, ints.iter().collect::<Vec<_>>().as_slice())
-            };
-            let sum_t_plus_1 = {
-                let ints = vars_t_plus_1
-                    .map(|flooded| flooded.ite(&Int::from_u64(&ctx, 1), &Int::from_u64(&ctx, 0)))
-                    .collect::<Vec<_>>();
-                Int::add(&ctx, ints.iter().collect::<Vec<_>>().as_slice())
-            };
+            let sum_t = count_flooded(t);
+            let sum_t_plus_1 = count_flooded(t + 1);
 
             assert(&Bool::or(
-                &ctx,
+                ctx,
                 &[&sum_t._eq(&num_clusters), &sum_t_plus_1.gt(&sum_t)],
             ));
         }
@@ -192,15 +287,14 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
 
     // ASSERT FLOOD VARS (PER CLUSTER)
     for (idx, cluster) in clusters.iter().enumerate() {
-        let neighbour_indices =
-            cluster.neighbour_clusters(clusters.as_slice(), instance.height(), instance.width());
+        let neighbour_indices = clustering.neighbours(idx);
 
         let cluster_flooded_vars = &flooded_vars[idx];
 
         // every cluster must be flooded at last
         assert(cluster_flooded_vars.last().unwrap());
 
-        if idx == start_cluster_idx {
+        if start_flooded[idx] {
             for a in cluster_flooded_vars.iter() {
                 assert(a);
             }
@@ -249,7 +343,6 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
     if optimize {
         let optimization_goal = {
             let nums: Vec<_> = (0..=t_max)
-                .into_iter()
                 .map(|t| {
                     let flooded_vars_t: Vec<_> = flooded_vars.iter().map(|vars| &vars[t]).collect();
                     let all_flooded_t = Bool::and(ctx, flooded_vars_t.as_slice());
@@ -261,7 +354,7 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
         };
 
         solver.maximize(&optimization_goal);
-    } else {
+    } else if t_max > 0 {
         let flooding_at_t_minus_one: Vec<_> =
             flooded_vars.iter().map(|vars| &vars[t_max - 1]).collect();
         let not_all_flooded_at_t_minus_one = Bool::and(ctx, &flooding_at_t_minus_one).not();
@@ -284,6 +377,14 @@ pub fn init_solver<'ctx, T: Solver<'ctx>>(
 pub fn run_solver<'c, T: Solver<'c>>(
     state: SolverState<'c, T>,
     t_max: usize,
+) -> (z3::SatResult, Option<Solution>) {
+    check(&state, t_max)
+}
+
+/// Like [run_solver], but keeps the solver alive, e.g. to ask why a check returned `Unknown`
+fn check<'c, T: Solver<'c>>(
+    state: &SolverState<'c, T>,
+    t_max: usize,
 ) -> (z3::SatResult, Option<Solution>) {
     let SolverState {
         solver,
@@ -315,16 +416,10 @@ pub fn run_solver<'c, T: Solver<'c>>(
 
                 let solution_length = (0..flood_model.len())
                     .into_iter()
-                    .position(|i| {
-                        flood_model
-                            .iter()
-                            .map(|vars| vars[i])
-                            .all(|flooded| flooded)
-                    })
+                    .position(|i| flood_model.iter().all(|vars| vars[i]))
                     .unwrap_or(t_max);
 
                 let color_model = (0..t_max)
-                    .into_iter()
                     .map(|idx| {
                         model
                             .eval(&color_vars[idx], false)
@@ -345,3 +440,500 @@ pub fn run_solver<'c, T: Solver<'c>>(
         }
     }
 }
+
+/// Counts the distinct solutions of exactly `t_max` moves, but no more than `limit`
+///
+/// Every found solution is excluded by a blocking clause before searching for the next one. If
+/// `t_max` is the optimal solution length, the result is the number of optimal solutions.
+///
+/// The returned [z3::SatResult] is `Sat` if at least one solution was found, `Unsat` if there is
+/// none and `Unknown` if z3 gave up before counting was finished.
+pub fn count_solutions<'c, T: Solver<'c>>(
+    ctx: &'c z3::Context,
+    instance: &Problem,
+    t_max: usize,
+    limit: usize,
+) -> (z3::SatResult, usize) {
+    let SolverState {
+        solver,
+        model: Model {
+            colors: color_vars, ..
+        },
+        ..
+    } = init_solver::<T>(ctx, instance, t_max, false);
+
+    let mut count = 0;
+    while count < limit {
+        match solver.check() {
+            z3::SatResult::Sat => {}
+            z3::SatResult::Unsat if count > 0 => return (z3::SatResult::Sat, count),
+            result => return (result, count),
+        }
+
+        let Some(model) = solver.get_model() else {
+            return (z3::SatResult::Unknown, count);
+        };
+        count += 1;
+
+        // at least one color must differ from the found solution
+        let differs: Vec<Bool> = color_vars
+            .iter()
+            .filter_map(|var| model.eval(var, false).map(|value| var._eq(&value).not()))
+            .collect();
+        solver.assert(&Bool::or(
+            ctx,
+            differs.iter().collect::<Vec<_>>().as_slice(),
+        ));
+    }
+
+    (z3::SatResult::Sat, count)
+}
+
+/// Mode of finding an (optimal) solution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Use the z3 optimizer to find a minimal solution, optionally below an upper bound
+    Opt { upper_bound: Option<usize> },
+    /// Find a minimal solution by binary search in reasonable bounds
+    #[default]
+    Min,
+    /// Find a minimal solution by binary search in the given bounds
+    Search {
+        lower_bound: usize,
+        upper_bound: usize,
+    },
+    /// Find a solution with exact size
+    Exact { size: usize },
+    /// Find a solution with reasonable large size
+    Solve,
+}
+
+impl Strategy {
+    /// Name of the subcommand selecting this strategy
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Opt { .. } => "opt",
+            Strategy::Min => "min",
+            Strategy::Search { .. } => "search",
+            Strategy::Exact { .. } => "exact",
+            Strategy::Solve => "solve",
+        }
+    }
+
+    pub fn use_optimizer(&self) -> bool {
+        matches!(self, Strategy::Opt { .. })
+    }
+
+    // Get bounds defined by strategy with given fallback values `lo` and `hi`
+    pub fn get_bounds(&self, lo: usize, hi: usize) -> (usize, usize) {
+        match self {
+            Strategy::Opt {
+                upper_bound: Some(upper_bound),
+            } => (*upper_bound, *upper_bound),
+            Strategy::Opt { upper_bound: None } => (hi, hi),
+            Strategy::Min => (lo, hi),
+            Strategy::Search {
+                lower_bound,
+                upper_bound,
+            } => (*lower_bound, *upper_bound),
+            Strategy::Exact { size } => (*size, *size),
+            Strategy::Solve => (hi, hi),
+        }
+    }
+}
+
+/// Parameters of [solve]
+#[derive(Debug, Clone, Default)]
+pub struct SolveOptions {
+    pub strategy: Strategy,
+    pub encoding: Encoding,
+    /// Moves already played, the search finds their optimal continuation
+    pub prefix: Vec<Color>,
+    /// Time after which no further z3 run is started and a running one is interrupted
+    pub time_limit: Option<Duration>,
+    /// Stops the search early when cancelled, like an exceeded time limit
+    ///
+    /// # Example
+    /// ```
+    /// use color_flood_rs::problem::Problem;
+    /// use color_flood_rs::solver::{solve, CancelHandle, SolveOptions};
+    ///
+    /// let instance: Problem = "010\n100\n012".parse().unwrap();
+    /// let cancel = CancelHandle::new();
+    /// cancel.cancel();
+    /// let options = SolveOptions { cancel: Some(cancel), ..Default::default() };
+    /// let outcome = solve(&instance, &options);
+    /// assert!(outcome.attempts.is_empty());
+    /// assert!(outcome.solution.is_none());
+    /// ```
+    pub cancel: Option<CancelHandle>,
+}
+
+/// A single z3 run of a search
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// Solution length z3 was run with
+    pub t: usize,
+    pub result: z3::SatResult,
+    /// Time z3 spent checking
+    pub time: Duration,
+    /// Time spent building the encoding
+    pub encoding_time: Duration,
+    /// Number of assertions of the encoding
+    pub asserts: usize,
+}
+
+/// Result of [solve]
+#[derive(Clone)]
+pub struct SolveOutcome {
+    /// Result of the last z3 run, or the last satisfiable one, if any
+    pub result: z3::SatResult,
+    /// The shortest solution found. Continues the prefix, if one was given
+    pub solution: Option<Solution>,
+    /// Solution bounds the search started with
+    pub bounds: (usize, usize),
+    /// If the solution is proven to be of minimal length
+    pub optimal: bool,
+    /// Every z3 run performed during the search
+    pub attempts: Vec<Attempt>,
+}
+
+impl SolveOutcome {
+    /// Total time spent building encodings
+    pub fn encoding_time(&self) -> Duration {
+        self.attempts
+            .iter()
+            .map(|attempt| attempt.encoding_time)
+            .sum()
+    }
+
+    /// Total time z3 spent checking
+    pub fn check_time(&self) -> Duration {
+        self.attempts.iter().map(|attempt| attempt.time).sum()
+    }
+
+    /// Number of z3 checks performed
+    pub fn checks(&self) -> usize {
+        self.attempts.len()
+    }
+}
+
+/// Receives the events of a running [solve_with]
+///
+/// All methods do nothing by default, so implementors only override the events they are
+/// interested in.
+pub trait Observer {
+    /// The encoding for solution length `t` was built with `asserts` assertions
+    fn encoded(&mut self, _t: usize, _asserts: usize) {}
+
+    /// z3 started checking for a solution of length `t`
+    fn check_started(&mut self, _t: usize) {}
+
+    /// z3 finished a check
+    fn check_finished(&mut self, _attempt: &Attempt) {}
+
+    /// The solution length searched for is narrowed down to `[lo, hi]`. Sent before the first
+    /// check and after every check that doesn't end the search, but never with `lo > hi`
+    fn bounds_updated(&mut self, _lo: usize, _hi: usize) {}
+
+    /// A solution shorter than all solutions found before was found
+    fn solution_found(&mut self, _solution: &Solution) {}
+}
+
+/// Ignores all events
+impl Observer for () {}
+
+/// Solution bounds a search with the given options starts with
+///
+/// The upper bound is
+///
+/// min { 2n + (√2c)n + c, c * (n − 1) } ⋃ { #clusters }
+///
+/// See <https://arxiv.org/pdf/1001.4420.pdf> Section 6.
+pub fn solution_bounds(instance: &Problem, options: &SolveOptions) -> (usize, usize) {
+    let max_moves = {
+        let num_clusters = Cluster::from_problem(&instance.after(&options.prefix)).len();
+        let n = instance.height().max(instance.width());
+        let c = instance.num_colors();
+        [
+            num_clusters,
+            // upper bound
+            c * (n - 1),
+            // asymptotic upper bound
+            2 * n + c + ((2 * c) as f32).sqrt().ceil() as usize * n,
+        ]
+        .into_iter()
+        .min()
+        .unwrap()
+    };
+
+    options.strategy.get_bounds(0, max_moves)
+}
+
+/// Assertions of the first z3 run of [solve] in SMT-LIB format
+pub fn initial_asserts(instance: &Problem, options: &SolveOptions) -> Vec<String> {
+    let ctx = z3::Context::new(&Default::default());
+    let (lo, hi) = solution_bounds(instance, options);
+    let flooded = instance.after(&options.prefix).flooded_region();
+    let optimize = options.strategy.use_optimizer();
+
+    let to_strings = |asserts: &[Bool]| asserts.iter().map(ToString::to_string).collect();
+    if optimize {
+        let state = init_solver_from::<z3::Optimize>(
+            &ctx,
+            instance,
+            &flooded,
+            (lo + hi) / 2,
+            optimize,
+            options.encoding,
+        );
+        to_strings(state.get_asserts())
+    } else {
+        let state = init_solver_from::<z3::Solver>(
+            &ctx,
+            instance,
+            &flooded,
+            (lo + hi) / 2,
+            optimize,
+            options.encoding,
+        );
+        to_strings(state.get_asserts())
+    }
+}
+
+/// Solves an instance via optimization or by performing binary search over the solution length
+///
+/// Every call runs in a fresh z3 context, so instances can be solved in parallel.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::problem::Problem;
+/// use color_flood_rs::solver::{solve, SolveOptions, Strategy};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let options = SolveOptions { strategy: Strategy::Min, ..Default::default() };
+/// let outcome = solve(&instance, &options);
+/// assert_eq!(outcome.solution.unwrap().colors.len(), 4);
+/// assert!(outcome.optimal);
+/// ```
+pub fn solve(instance: &Problem, options: &SolveOptions) -> SolveOutcome {
+    solve_with(instance, options, &mut ())
+}
+
+/// Like [solve], but reports the progress of the search to `observer`
+pub fn solve_with<O: Observer>(
+    instance: &Problem,
+    options: &SolveOptions,
+    observer: &mut O,
+) -> SolveOutcome {
+    let mut cfg = z3::Config::new();
+    if let Some(time_limit) = options.time_limit {
+        cfg.set_timeout_msec(time_limit.as_millis() as u64);
+    }
+    let ctx = z3::Context::new(&cfg);
+    let deadline = options
+        .time_limit
+        .map(|time_limit| Instant::now() + time_limit);
+
+    std::thread::scope(|scope| {
+        // interrupt running z3 checks once the time limit is exceeded or the search is cancelled,
+        // until the search is done
+        let (done, watchdog) = mpsc::channel::<()>();
+        if deadline.is_some() || options.cancel.is_some() {
+            let handle = ctx.handle();
+            scope.spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    watchdog.recv_timeout(WATCHDOG_INTERVAL)
+                {
+                    if stopped(options, deadline) {
+                        handle.interrupt();
+                    }
+                }
+            });
+        }
+
+        let outcome = if options.strategy.use_optimizer() {
+            binary_search::<z3::Optimize, _>(&ctx, instance, options, deadline, observer)
+        } else {
+            binary_search::<z3::Solver, _>(&ctx, instance, options, deadline, observer)
+        };
+        drop(done);
+        outcome
+    })
+}
+
+/// Time between two checks of the watchdog of [solve_with] for an exceeded time limit or a
+/// cancelled search
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(50);
+
+/// If a search must not start any further z3 runs
+fn stopped(options: &SolveOptions, deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        || options
+            .cancel
+            .as_ref()
+            .is_some_and(CancelHandle::is_cancelled)
+}
+
+/// Searches the solution length within [solution_bounds]. A single z3 run if the bounds are equal
+fn binary_search<'c, T: Solver<'c>, O: Observer>(
+    ctx: &'c z3::Context,
+    instance: &Problem,
+    options: &SolveOptions,
+    deadline: Option<Instant>,
+    observer: &mut O,
+) -> SolveOutcome {
+    let optimize = options.strategy.use_optimizer();
+    let flooded = instance.after(&options.prefix).flooded_region();
+
+    // Moving bounds for binary search
+    let bounds = solution_bounds(instance, options);
+    let (mut lo, mut hi) = bounds;
+
+    // t := solution size (= (max) number of colors in solution's color sequence)
+    let mut t = (hi + lo) / 2;
+    let mut attempts: Vec<Attempt> = vec![];
+    let mut interrupted = false;
+    if lo <= hi {
+        observer.bounds_updated(lo, hi);
+    }
+
+    let (result, solution) = {
+        let mut ret = (z3::SatResult::Unknown, None);
+        loop {
+            if stopped(options, deadline) {
+                break ret;
+            }
+
+            let start = Instant::now();
+            let solver_state =
+                init_solver_from::<T>(ctx, instance, &flooded, t, optimize, options.encoding);
+            let encoding_time = start.elapsed();
+            let asserts = solver_state.get_asserts().len();
+            observer.encoded(t, asserts);
+            // building large encodings takes a while, so don't start a check that isn't wanted
+            if stopped(options, deadline) {
+                break ret;
+            }
+
+            observer.check_started(t);
+            let start = Instant::now();
+            let tmp = check(&solver_state, t);
+            let time = start.elapsed();
+            // z3::Optimize can't be kept from handling Ctrl-C itself by interrupting the check,
+            // which is taken as a cancellation
+            if tmp.0 == z3::SatResult::Unknown
+                && !stopped(options, deadline)
+                && solver_state
+                    .solver
+                    .reason_unknown()
+                    .is_some_and(|reason| reason.contains("canceled"))
+            {
+                if let Some(cancel) = &options.cancel {
+                    cancel.cancel();
+                }
+                interrupted = true;
+            }
+            let attempt = Attempt {
+                t,
+                result: tmp.0,
+                time,
+                encoding_time,
+                asserts,
+            };
+            observer.check_finished(&attempt);
+            attempts.push(attempt);
+
+            match tmp.0 {
+                z3::SatResult::Unsat => {
+                    lo = t + 1;
+                }
+                z3::SatResult::Unknown => {
+                    lo = t + 1;
+                }
+                z3::SatResult::Sat => {
+                    ret = tmp.clone();
+                    if let Some(solution) = &ret.1 {
+                        observer.solution_found(solution);
+                    }
+                    // there is no shorter solution than the empty one
+                    if t == 0 {
+                        break ret;
+                    }
+                    hi = t - 1;
+                }
+            }
+            if lo <= hi {
+                observer.bounds_updated(lo, hi);
+            }
+
+            t = (hi + lo) / 2;
+
+            if lo > hi || interrupted || stopped(options, deadline) {
+                if ret.0 == z3::SatResult::Sat {
+                    break ret;
+                } else {
+                    break tmp;
+                }
+            }
+        }
+    };
+
+    // the optimizer always finds a minimal solution, otherwise a minimal solution is proven by
+    // the absence of a solution that is one move shorter
+    let optimal = match &solution {
+        Some(solution) if result == z3::SatResult::Sat => {
+            let shorter = solution.colors.len().saturating_sub(1);
+            optimize
+                || solution.colors.is_empty()
+                || attempts
+                    .iter()
+                    .any(|attempt| attempt.t == shorter && attempt.result == z3::SatResult::Unsat)
+        }
+        _ => false,
+    };
+
+    SolveOutcome {
+        result,
+        solution,
+        bounds,
+        optimal,
+        attempts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records every bounds update
+    #[derive(Default)]
+    struct Bounds(Vec<(usize, usize)>);
+
+    impl Observer for Bounds {
+        fn bounds_updated(&mut self, lo: usize, hi: usize) {
+            self.0.push((lo, hi));
+        }
+    }
+
+    #[test]
+    fn never_reports_crossed_bounds() {
+        let instance: Problem = "012\n120\n201\n".parse().unwrap();
+        for (lower_bound, upper_bound) in [(5, 3), (0, 6), (4, 4)] {
+            let options = SolveOptions {
+                strategy: Strategy::Search {
+                    lower_bound,
+                    upper_bound,
+                },
+                ..Default::default()
+            };
+            let mut observer = Bounds::default();
+            solve_with(&instance, &options, &mut observer);
+            assert!(
+                observer.0.iter().all(|(lo, hi)| lo <= hi),
+                "{:?}",
+                observer.0
+            );
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Rendering boards and solutions as SVG images
+
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{
+    colorizer::Colorizer,
+    problem::{Color, Problem},
+    solution::Solution,
+    util::Point,
+};
+
+/// Appearance of rendered images
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// Edge length of a single tile in pixels
+    pub tile_size: u32,
+    /// Draw a thin outline around every tile
+    pub outlines: bool,
+    /// Draw a thick border around the flooded region
+    pub highlight_flooded: bool,
+    /// Label every board of a solution with the color chosen and its move number
+    pub move_numbers: bool,
+    /// Number of boards per row when rendering a solution
+    pub columns: usize,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            tile_size: 24,
+            outlines: true,
+            highlight_flooded: true,
+            move_numbers: true,
+            columns: 4,
+        }
+    }
+}
+
+/// Renders a single board
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{problem::Problem, svg::{render_board, SvgOptions}};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let svg = render_board(&instance, &SvgOptions::default());
+/// assert!(svg.starts_with("<svg"));
+/// assert_eq!(svg.matches("<rect x=").count(), 9);
+/// ```
+pub fn render_board(instance: &Problem, options: &SvgOptions) -> String {
+    let tile = options.tile_size;
+    let (width, height) = (
+        instance.width() as u32 * tile,
+        instance.height() as u32 * tile,
+    );
+
+    let mut svg = String::new();
+    open_svg(&mut svg, width, height);
+    write_board(&mut svg, instance, options, 0, 0);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Renders the initial board and the board after every move of a solution
+///
+/// Boards are laid out in rows of `options.columns` boards.
+///
+/// # Example
+/// ```
+/// use color_flood_rs::{problem::Problem, solution::Solution, svg::{render_solution, SvgOptions}};
+///
+/// let instance: Problem = "010\n100\n012".parse().unwrap();
+/// let svg = render_solution(&instance, &Solution::from([1, 0, 2, 1]), &SvgOptions::default());
+/// assert_eq!(svg.matches("<g ").count(), 5);
+/// ```
+pub fn render_solution(instance: &Problem, solution: &Solution, options: &SvgOptions) -> String {
+    let tile = options.tile_size;
+    let columns = options.columns.max(1);
+    let boards = solution.colors.len() + 1;
+
+    let label_height = if options.move_numbers { tile } else { 0 };
+    let gap = tile / 2;
+    let frame_width = instance.width() as u32 * tile;
+    let frame_height = instance.height() as u32 * tile + label_height;
+
+    let grid_columns = columns.min(boards) as u32;
+    let grid_rows = boards.div_ceil(columns) as u32;
+    let width = grid_columns * (frame_width + gap) - gap;
+    let height = grid_rows * (frame_height + gap) - gap;
+
+    let mut svg = String::new();
+    open_svg(&mut svg, width, height);
+
+    let mut instance = instance.clone();
+    for step in 0..boards {
+        let color = step.checked_sub(1).map(|idx| solution.colors[idx]);
+        if let Some(color) = color {
+            instance.apply_color(color);
+        }
+
+        let x = (step % columns) as u32 * (frame_width + gap);
+        let y = (step / columns) as u32 * (frame_height + gap);
+        writeln!(svg, r#"<g transform="translate({x},{y})">"#).unwrap();
+
+        if options.move_numbers {
+            write_label(&mut svg, step, color, tile);
+        }
+        write_board(&mut svg, &instance, options, 0, label_height);
+
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes the opening `svg` tag and a white background
+fn open_svg(svg: &mut String, width: u32, height: u32) {
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+}
+
+/// Writes a swatch of the chosen color and the move number above a board
+fn write_label(svg: &mut String, step: usize, color: Option<Color>, tile: u32) {
+    let colorizer = Colorizer::new();
+    let radius = tile / 4;
+    let baseline = tile * 3 / 4;
+
+    let mut text_x = 0;
+    if let Some(color) = color {
+        writeln!(
+            svg,
+            r#"<circle cx="{radius}" cy="{}" r="{radius}" fill="{}" stroke="black"/>"#,
+            tile / 2,
+            hex(colorizer.rgb(color as usize))
+        )
+        .unwrap();
+        text_x = 3 * radius;
+    }
+
+    writeln!(
+        svg,
+        r#"<text x="{text_x}" y="{baseline}" font-family="sans-serif" font-size="{}">{step}</text>"#,
+        tile * 2 / 3
+    )
+    .unwrap();
+}
+
+/// Writes the tiles of a board with its top left corner at `(x0, y0)`
+fn write_board(svg: &mut String, instance: &Problem, options: &SvgOptions, x0: u32, y0: u32) {
+    let colorizer = Colorizer::new();
+    let tile = options.tile_size;
+    let outline = if options.outlines {
+        r#" stroke="black" stroke-opacity="0.25""#
+    } else {
+        ""
+    };
+
+    for (y, row) in instance.grid.iter().enumerate() {
+        for (x, color) in row.iter().enumerate() {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{tile}" height="{tile}" fill="{}"{outline}/>"#,
+                x0 + x as u32 * tile,
+                y0 + y as u32 * tile,
+                hex(colorizer.rgb(*color as usize)),
+            )
+            .unwrap();
+        }
+    }
+
+    if options.highlight_flooded {
+        let region = instance.flooded_region();
+        writeln!(
+            svg,
+            r#"<path d="{}" fill="none" stroke="black" stroke-width="{}" stroke-linecap="square"/>"#,
+            region_border(&region, x0, y0, tile),
+            (tile / 8).max(1)
+        )
+        .unwrap();
+    }
+}
+
+/// Path data of all tile edges between the region and the remaining tiles or the board's border
+fn region_border(region: &HashSet<Point>, x0: u32, y0: u32, tile: u32) -> String {
+    let mut tiles: Vec<_> = region.iter().copied().collect();
+    tiles.sort_unstable();
+
+    let mut path = String::new();
+    for (y, x) in tiles {
+        let contains = |dy: i32, dx: i32| {
+            let (y, x) = (y as i64 + dy as i64, x as i64 + dx as i64);
+            y >= 0 && x >= 0 && region.contains(&(y as usize, x as usize))
+        };
+        let (left, top) = (x0 + x as u32 * tile, y0 + y as u32 * tile);
+
+        if !contains(-1, 0) {
+            write!(path, "M{left} {top}h{tile}").unwrap();
+        }
+        if !contains(1, 0) {
+            write!(path, "M{left} {}h{tile}", top + tile).unwrap();
+        }
+        if !contains(0, -1) {
+            write!(path, "M{left} {top}v{tile}").unwrap();
+        }
+        if !contains(0, 1) {
+            write!(path, "M{} {top}v{tile}", left + tile).unwrap();
+        }
+    }
+    path
+}
+
+/// Formats an RGB value as hex color
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
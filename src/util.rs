@@ -1,14 +1,103 @@
-/// A point on a two dimensional grid
-pub type Point = (u8, u8);
+/// A point on a two dimensional grid, as (row, column)
+pub type Point = (usize, usize);
 
-/// Find all neighbour coords of `(y,x)` s.t. ∀ y,x: y > 0 && x > 0 && y < height && x < height
+/// Find all neighbour coords of `(y,x)` s.t. ∀ y,x: y >= 0 && x >= 0 && y < height && x < width
 #[inline(always)]
-pub(crate) fn neighbours(y: u8, x: u8, height: usize, width: usize) -> Vec<Point> {
-    let (y, x) = (y as i8, x as i8);
-    [(0, 1), (0, -1), (1, 0), (-1, 0)]
-        .into_iter()
-        .map(|(dy, dx)| (y + dy, x + dx))
-        .filter(|&(y, x)| y >= 0 && x >= 0 && y < height as i8 && x < width as i8)
-        .map(|(y, x)| (y as u8, x as u8))
-        .collect()
+pub(crate) fn neighbours(
+    y: usize,
+    x: usize,
+    height: usize,
+    width: usize,
+) -> impl Iterator<Item = Point> {
+    // called for every tile while clustering, so don't allocate
+    [
+        (x + 1 < width).then(|| (y, x + 1)),
+        (x > 0).then(|| (y, x - 1)),
+        (y + 1 < height).then(|| (y + 1, x)),
+        (y > 0).then(|| (y - 1, x)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// A small, seedable pseudo random number generator (SplitMix64)
+///
+/// Used instead of an external crate so that generated output stays reproducible for a given seed.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `[0, n)`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a number in `[0, 1)`
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(points: impl Iterator<Item = Point>) -> Vec<Point> {
+        let mut points: Vec<Point> = points.collect();
+        points.sort_unstable();
+        points
+    }
+
+    #[test]
+    fn neighbours_of_corners() {
+        assert_eq!(sorted(neighbours(0, 0, 3, 4)), vec![(0, 1), (1, 0)]);
+        assert_eq!(sorted(neighbours(0, 3, 3, 4)), vec![(0, 2), (1, 3)]);
+        assert_eq!(sorted(neighbours(2, 0, 3, 4)), vec![(1, 0), (2, 1)]);
+        assert_eq!(sorted(neighbours(2, 3, 3, 4)), vec![(1, 3), (2, 2)]);
+    }
+
+    #[test]
+    fn neighbours_along_edges() {
+        assert_eq!(sorted(neighbours(0, 1, 3, 4)), vec![(0, 0), (0, 2), (1, 1)]);
+        assert_eq!(sorted(neighbours(2, 2, 3, 4)), vec![(1, 2), (2, 1), (2, 3)]);
+        assert_eq!(sorted(neighbours(1, 0, 3, 4)), vec![(0, 0), (1, 1), (2, 0)]);
+        assert_eq!(sorted(neighbours(1, 3, 3, 4)), vec![(0, 3), (1, 2), (2, 3)]);
+        assert_eq!(
+            sorted(neighbours(1, 1, 3, 4)),
+            vec![(0, 1), (1, 0), (1, 2), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn neighbours_in_a_single_row_or_column() {
+        assert_eq!(sorted(neighbours(0, 0, 1, 1)), vec![]);
+        assert_eq!(sorted(neighbours(0, 0, 1, 3)), vec![(0, 1)]);
+        assert_eq!(sorted(neighbours(0, 1, 1, 3)), vec![(0, 0), (0, 2)]);
+        assert_eq!(sorted(neighbours(0, 2, 1, 3)), vec![(0, 1)]);
+        assert_eq!(sorted(neighbours(0, 0, 3, 1)), vec![(1, 0)]);
+        assert_eq!(sorted(neighbours(1, 0, 3, 1)), vec![(0, 0), (2, 0)]);
+        assert_eq!(sorted(neighbours(2, 0, 3, 1)), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn neighbours_beyond_255() {
+        assert_eq!(
+            sorted(neighbours(499, 300, 500, 500)),
+            vec![(498, 300), (499, 299), (499, 301)]
+        );
+    }
 }